@@ -0,0 +1,204 @@
+use std::cmp::Ordering;
+
+/// an arbitrary-precision integer, a sign and base 2^32 digits with the least significant first
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct BigInt {
+    neg: bool,
+    digits: Vec<u32>,
+}
+impl BigInt {
+    fn new(neg: bool, mut digits: Vec<u32>) -> Self {
+        while digits.last() == Some(&0) { digits.pop(); }
+        let neg = neg && !digits.is_empty();
+        Self { neg, digits }
+    }
+    pub fn is_zero(&self) -> bool {
+        self.digits.is_empty()
+    }
+    pub fn from_i64(v: i64) -> Self {
+        let m = v.unsigned_abs();
+        Self::new(v < 0, vec![m as u32, (m >> 32) as u32])
+    }
+    pub fn to_i64(&self) -> Option<i64> {
+        if self.digits.len() > 2 { return None }
+        let m = self.digits.iter().rev().fold(0u64, |acc, d| (acc << 32) | *d as u64);
+        if self.neg {
+            if m <= 1 << 63 { Some((m as i64).wrapping_neg()) } else { None }
+        } else {
+            i64::try_from(m).ok()
+        }
+    }
+    pub fn from_f64(v: f64) -> Option<Self> {
+        if !v.is_finite() { return None }
+        let mut m = v.abs().trunc();
+        let mut digits: Vec<u32> = vec![];
+        while m >= 1.0 {
+            digits.push((m % 4294967296.0) as u32);
+            m = (m / 4294967296.0).trunc();
+        }
+        Some(Self::new(v < 0.0, digits))
+    }
+    pub fn to_f64(&self) -> f64 {
+        let m = self.digits.iter().rev().fold(0.0, |acc, d| acc * 4294967296.0 + *d as f64);
+        if self.neg { -m } else { m }
+    }
+    /// parses a decimal integer with an optional sign
+    pub fn parse(s: &str) -> Option<Self> {
+        let (neg, s) = match s.strip_prefix('-') {
+            Some(s) => (true, s),
+            None => (false, s.strip_prefix('+').unwrap_or(s))
+        };
+        if s.is_empty() { return None }
+        let mut digits: Vec<u32> = vec![];
+        for c in s.chars() {
+            let d = c.to_digit(10)?;
+            let mut carry = d as u64;
+            for digit in digits.iter_mut() {
+                let v = *digit as u64 * 10 + carry;
+                *digit = v as u32;
+                carry = v >> 32;
+            }
+            if carry > 0 { digits.push(carry as u32) }
+        }
+        Some(Self::new(neg, digits))
+    }
+
+    fn cmp_mag(a: &[u32], b: &[u32]) -> Ordering {
+        a.len().cmp(&b.len()).then_with(|| a.iter().rev().cmp(b.iter().rev()))
+    }
+    fn add_mag(a: &[u32], b: &[u32]) -> Vec<u32> {
+        let mut digits: Vec<u32> = vec![];
+        let mut carry = 0u64;
+        for i in 0..a.len().max(b.len()) {
+            let v = *a.get(i).unwrap_or(&0) as u64 + *b.get(i).unwrap_or(&0) as u64 + carry;
+            digits.push(v as u32);
+            carry = v >> 32;
+        }
+        if carry > 0 { digits.push(carry as u32) }
+        digits
+    }
+    /// `a - b` for `a >= b`
+    fn sub_mag(a: &[u32], b: &[u32]) -> Vec<u32> {
+        let mut digits: Vec<u32> = vec![];
+        let mut borrow = 0i64;
+        for (i, x) in a.iter().enumerate() {
+            let mut v = *x as i64 - *b.get(i).unwrap_or(&0) as i64 - borrow;
+            borrow = if v < 0 { v += 1 << 32; 1 } else { 0 };
+            digits.push(v as u32);
+        }
+        while digits.last() == Some(&0) { digits.pop(); }
+        digits
+    }
+    fn mul_mag(a: &[u32], b: &[u32]) -> Vec<u32> {
+        let mut digits = vec![0u32; a.len() + b.len()];
+        for (i, x) in a.iter().enumerate() {
+            let mut carry = 0u64;
+            for (j, y) in b.iter().enumerate() {
+                let v = digits[i + j] as u64 + *x as u64 * *y as u64 + carry;
+                digits[i + j] = v as u32;
+                carry = v >> 32;
+            }
+            digits[i + b.len()] = carry as u32;
+        }
+        digits
+    }
+    /// long division bit by bit, `b` must not be zero
+    fn div_rem_mag(a: &[u32], b: &[u32]) -> (Vec<u32>, Vec<u32>) {
+        let mut quotient = vec![0u32; a.len()];
+        let mut rem: Vec<u32> = vec![];
+        for i in (0..a.len() * 32).rev() {
+            // rem = rem * 2 + bit i of a
+            let mut carry = (a[i / 32] >> (i % 32)) & 1;
+            for digit in rem.iter_mut() {
+                let next = *digit >> 31;
+                *digit = (*digit << 1) | carry;
+                carry = next;
+            }
+            if carry > 0 { rem.push(carry) }
+            if Self::cmp_mag(&rem, b) != Ordering::Less {
+                rem = Self::sub_mag(&rem, b);
+                quotient[i / 32] |= 1 << (i % 32);
+            }
+        }
+        (quotient, rem)
+    }
+
+    pub fn neg(&self) -> Self {
+        Self::new(!self.neg, self.digits.clone())
+    }
+    pub fn abs(&self) -> Self {
+        Self::new(false, self.digits.clone())
+    }
+    pub fn add(&self, other: &Self) -> Self {
+        if self.neg == other.neg {
+            return Self::new(self.neg, Self::add_mag(&self.digits, &other.digits))
+        }
+        match Self::cmp_mag(&self.digits, &other.digits) {
+            Ordering::Less => Self::new(other.neg, Self::sub_mag(&other.digits, &self.digits)),
+            _ => Self::new(self.neg, Self::sub_mag(&self.digits, &other.digits))
+        }
+    }
+    pub fn sub(&self, other: &Self) -> Self {
+        self.add(&other.neg())
+    }
+    pub fn mul(&self, other: &Self) -> Self {
+        Self::new(self.neg != other.neg, Self::mul_mag(&self.digits, &other.digits))
+    }
+    /// the quotient rounded toward zero and the remainder with the sign of `self`, like `i64`,
+    /// `None` when dividing by zero
+    pub fn div_rem(&self, other: &Self) -> Option<(Self, Self)> {
+        if other.is_zero() { return None }
+        let (quotient, rem) = Self::div_rem_mag(&self.digits, &other.digits);
+        Some((Self::new(self.neg != other.neg, quotient), Self::new(self.neg, rem)))
+    }
+    pub fn pow(&self, mut exp: u32) -> Self {
+        let mut base = self.clone();
+        let mut result = Self::from_i64(1);
+        while exp > 0 {
+            if exp & 1 == 1 { result = result.mul(&base) }
+            base = base.mul(&base);
+            exp >>= 1;
+        }
+        result
+    }
+}
+impl Ord for BigInt {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.neg, other.neg) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => Self::cmp_mag(&self.digits, &other.digits),
+            (true, true) => Self::cmp_mag(&other.digits, &self.digits),
+        }
+    }
+}
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl std::fmt::Display for BigInt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_zero() { return write!(f, "0") }
+        // split into chunks of 9 decimal digits
+        let mut chunks: Vec<u32> = vec![];
+        let mut digits = self.digits.clone();
+        while !digits.is_empty() {
+            let mut rem = 0u64;
+            for digit in digits.iter_mut().rev() {
+                let v = (rem << 32) | *digit as u64;
+                *digit = (v / 1_000_000_000) as u32;
+                rem = v % 1_000_000_000;
+            }
+            while digits.last() == Some(&0) { digits.pop(); }
+            chunks.push(rem as u32);
+        }
+        let mut s = String::new();
+        if self.neg { s.push('-') }
+        s.push_str(chunks.pop().unwrap().to_string().as_str());
+        for chunk in chunks.iter().rev() {
+            s.push_str(format!("{chunk:09}").as_str());
+        }
+        write!(f, "{s}")
+    }
+}
@@ -0,0 +1,116 @@
+use crate::*;
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// writes bytes like a `b"..."` literal, printable ASCII stays as it is
+pub fn bytes_repr(bytes: &[u8]) -> String {
+    let mut s = String::from("b\"");
+    for b in bytes {
+        match b {
+            b'\n' => s.push_str("\\n"),
+            b'\t' => s.push_str("\\t"),
+            b'\r' => s.push_str("\\r"),
+            b'\\' => s.push_str("\\\\"),
+            b'"' => s.push_str("\\\""),
+            0x20..=0x7e => s.push(*b as char),
+            _ => s.push_str(format!("\\x{b:02x}").as_str())
+        }
+    }
+    s.push('"');
+    s
+}
+pub fn hex_encode(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+pub fn hex_decode(s: &str) -> Option<Vec<u8>> {
+    if !s.len().is_multiple_of(2) || !s.is_ascii() { return None }
+    (0..s.len()).step_by(2).map(|i| u8::from_str_radix(&s[i..i+2], 16).ok()).collect()
+}
+pub fn base64_encode(bytes: &[u8]) -> String {
+    let mut s = String::new();
+    for chunk in bytes.chunks(3) {
+        let n = chunk.iter().enumerate().fold(0u32, |n, (i, b)| n | (*b as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                s.push(BASE64[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                s.push('=');
+            }
+        }
+    }
+    s
+}
+/// decodes padded base64, whitespace is ignored
+pub fn base64_decode(s: &str) -> Option<Vec<u8>> {
+    let chars: Vec<u8> = s.bytes().filter(|c| !c.is_ascii_whitespace()).collect();
+    if !chars.len().is_multiple_of(4) { return None }
+    let mut bytes: Vec<u8> = vec![];
+    for (i, chunk) in chars.chunks(4).enumerate() {
+        let last = i == chars.len() / 4 - 1;
+        let padding = chunk.iter().rev().take_while(|c| **c == b'=').count();
+        if padding > 2 || (padding > 0 && !last) { return None }
+        let mut n = 0u32;
+        for c in &chunk[..4 - padding] {
+            n = n << 6 | BASE64.iter().position(|b| b == c)? as u32;
+        }
+        n <<= 6 * padding as u32;
+        bytes.extend_from_slice(&[(n >> 16) as u8, (n >> 8) as u8, n as u8][..3 - padding]);
+    }
+    Some(bytes)
+}
+
+fn arg_bytes(context: &Context, id: &str) -> Vec<u8> {
+    match context.get(&id.to_string()) {
+        Some(V::Bytes(bytes)) => bytes.clone(),
+        _ => vec![]
+    }
+}
+fn decode_error(context: &mut Context, pos: &Position, what: &str) -> E {
+    context.trace(pos.clone());
+    E::Decode(what.into())
+}
+
+/// the UTF-8 bytes of a string
+pub fn str_encode(context: &mut Context, pos: &Position) -> Result<V, E> {
+    Ok(V::Bytes(arg_str(context, "self").into_bytes()))
+}
+/// the string of UTF-8 bytes
+pub fn bytes_decode(context: &mut Context, pos: &Position) -> Result<V, E> {
+    match String::from_utf8(arg_bytes(context, "self")) {
+        Ok(s) => Ok(V::String(s)),
+        Err(_) => Err(decode_error(context, pos, "UTF-8"))
+    }
+}
+pub fn bytes_hex(context: &mut Context, pos: &Position) -> Result<V, E> {
+    Ok(V::String(hex_encode(&arg_bytes(context, "self"))))
+}
+pub fn bytes_from_hex(context: &mut Context, pos: &Position) -> Result<V, E> {
+    match hex_decode(&arg_str(context, "s")) {
+        Some(bytes) => Ok(V::Bytes(bytes)),
+        None => Err(decode_error(context, pos, "hex"))
+    }
+}
+pub fn bytes_base64(context: &mut Context, pos: &Position) -> Result<V, E> {
+    Ok(V::String(base64_encode(&arg_bytes(context, "self"))))
+}
+pub fn bytes_from_base64(context: &mut Context, pos: &Position) -> Result<V, E> {
+    match base64_decode(&arg_str(context, "s")) {
+        Some(bytes) => Ok(V::Bytes(bytes)),
+        None => Err(decode_error(context, pos, "base64"))
+    }
+}
+/// the bytes from `start` up to `end` (or the end), negative positions count from the end
+pub fn bytes_slice(context: &mut Context, pos: &Position) -> Result<V, E> {
+    let bytes = arg_bytes(context, "self");
+    let len = bytes.len() as i64;
+    let bound = |i: i64| (if i < 0 { len + i } else { i }).clamp(0, len) as usize;
+    let start = match context.get(&String::from("start")) {
+        Some(V::Int(start)) => bound(*start),
+        _ => 0
+    };
+    let end = match context.get(&String::from("end")) {
+        Some(V::Int(end)) => bound(*end),
+        _ => len as usize
+    };
+    Ok(V::Bytes(if start < end { bytes[start..end].to_vec() } else { vec![] }))
+}
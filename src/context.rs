@@ -149,6 +149,138 @@ pub fn str_join(context: &mut Context, pos: &Position) -> Result<V, E> {
         Err(E::ExpectedType(Type::String, s.typ()))
     }
 }
+/// a `str` argument, empty if it is missing
+pub fn arg_str(context: &Context, id: &str) -> String {
+    match context.get(&id.to_string()) {
+        Some(V::String(s)) => s.clone(),
+        _ => String::new()
+    }
+}
+fn arg_int(context: &Context, id: &str) -> i64 {
+    match context.get(&id.to_string()) {
+        Some(V::Int(v)) => *v,
+        _ => 0
+    }
+}
+fn str_vector(strings: Vec<String>) -> V {
+    let types = if strings.is_empty() { vec![] } else { vec![Type::String] };
+    V::Vector(strings.into_iter().map(V::String).collect(), types)
+}
+pub fn str_split(context: &mut Context, pos: &Position) -> Result<V, E> {
+    let s = arg_str(context, "self");
+    match context.get(&String::from("sep")) {
+        Some(V::String(sep)) => Ok(str_vector(s.split(sep.as_str()).map(|x| x.to_string()).collect())),
+        _ => Ok(str_vector(s.split_whitespace().map(|x| x.to_string()).collect()))
+    }
+}
+pub fn str_replace(context: &mut Context, pos: &Position) -> Result<V, E> {
+    Ok(V::String(arg_str(context, "self").replace(arg_str(context, "from").as_str(), arg_str(context, "to").as_str())))
+}
+pub fn str_trim(context: &mut Context, pos: &Position) -> Result<V, E> {
+    Ok(V::String(arg_str(context, "self").trim().to_string()))
+}
+pub fn str_starts_with(context: &mut Context, pos: &Position) -> Result<V, E> {
+    Ok(V::Bool(arg_str(context, "self").starts_with(arg_str(context, "prefix").as_str())))
+}
+pub fn str_ends_with(context: &mut Context, pos: &Position) -> Result<V, E> {
+    Ok(V::Bool(arg_str(context, "self").ends_with(arg_str(context, "suffix").as_str())))
+}
+pub fn str_find(context: &mut Context, pos: &Position) -> Result<V, E> {
+    let s = arg_str(context, "self");
+    match s.find(arg_str(context, "sub").as_str()) {
+        Some(index) => Ok(V::Int(s[..index].chars().count() as i64)),
+        None => Ok(V::Null)
+    }
+}
+pub fn str_upper(context: &mut Context, pos: &Position) -> Result<V, E> {
+    Ok(V::String(arg_str(context, "self").to_uppercase()))
+}
+pub fn str_lower(context: &mut Context, pos: &Position) -> Result<V, E> {
+    Ok(V::String(arg_str(context, "self").to_lowercase()))
+}
+pub fn str_chars(context: &mut Context, pos: &Position) -> Result<V, E> {
+    Ok(str_vector(arg_str(context, "self").chars().map(|c| c.to_string()).collect()))
+}
+pub fn str_repeat(context: &mut Context, pos: &Position) -> Result<V, E> {
+    Ok(V::String(arg_str(context, "self").repeat(arg_int(context, "n").max(0) as usize)))
+}
+/// the characters from `start` up to `end` (or the end), negative positions count from the end
+pub fn str_slice(context: &mut Context, pos: &Position) -> Result<V, E> {
+    let chars = arg_str(context, "self").chars().collect::<Vec<char>>();
+    let len = chars.len() as i64;
+    let bound = |i: i64| (if i < 0 { len + i } else { i }).clamp(0, len) as usize;
+    let start = bound(arg_int(context, "start"));
+    let end = match context.get(&String::from("end")) {
+        Some(V::Int(end)) => bound(*end),
+        _ => len as usize
+    };
+    Ok(V::String(if start < end { chars[start..end].iter().collect() } else { String::new() }))
+}
+fn pad(context: &mut Context, left: bool) -> String {
+    let s = arg_str(context, "self");
+    let fill = match context.get(&String::from("fill")) {
+        Some(V::String(fill)) if !fill.is_empty() => fill.clone(),
+        _ => " ".into()
+    };
+    let missing = (arg_int(context, "width").max(0) as usize).saturating_sub(s.chars().count());
+    let padding = fill.chars().cycle().take(missing).collect::<String>();
+    if left { padding + &s } else { s + &padding }
+}
+pub fn str_pad_left(context: &mut Context, pos: &Position) -> Result<V, E> {
+    Ok(V::String(pad(context, true)))
+}
+pub fn str_pad_right(context: &mut Context, pos: &Position) -> Result<V, E> {
+    Ok(V::String(pad(context, false)))
+}
+/// replaces `{}` with the next argument and `{i}` with the i-th argument, `{{` and `}}` are plain braces
+pub fn str_format(context: &mut Context, pos: &Position) -> Result<V, E> {
+    let template = arg_str(context, "template");
+    let args = match context.get(&String::from("args")) {
+        Some(V::Vector(args, _)) => args.clone(),
+        _ => vec![]
+    };
+    let mut s = String::new();
+    let mut next: usize = 0;
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => { chars.next(); s.push('{') }
+            '}' if chars.peek() == Some(&'}') => { chars.next(); s.push('}') }
+            '{' => {
+                let mut index = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) if c.is_ascii_digit() => index.push(c),
+                        _ => {
+                            context.trace(pos.clone());
+                            return Err(E::Format(template))
+                        }
+                    }
+                }
+                let index = if index.is_empty() {
+                    next += 1;
+                    next - 1
+                } else {
+                    index.parse::<usize>().unwrap_or(usize::MAX)
+                };
+                match args.get(index) {
+                    Some(arg) => s.push_str(arg.to_string().as_str()),
+                    None => {
+                        context.trace(pos.clone());
+                        return Err(E::IndexRange(args.len(), index as i64))
+                    }
+                }
+            }
+            '}' => {
+                context.trace(pos.clone());
+                return Err(E::Format(template))
+            }
+            c => s.push(c)
+        }
+    }
+    Ok(V::String(s))
+}
 pub fn fs_read(context: &mut Context, pos: &Position) -> Result<V, E> {
     let path = context.get(&String::from("path"));
     if let Some(V::String(path)) = path {
@@ -160,31 +292,363 @@ pub fn fs_read(context: &mut Context, pos: &Position) -> Result<V, E> {
         Err(E::FileNotFound("<NO_FILE>".into()))
     }
 }
+pub fn fs_read_bytes(context: &mut Context, pos: &Position) -> Result<V, E> {
+    let path = arg_str(context, "path");
+    match fs::read(&path) {
+        Ok(bytes) => Ok(V::Bytes(bytes)),
+        Err(_) => {
+            context.trace(pos.clone());
+            Err(E::FileNotFound(path))
+        }
+    }
+}
+pub fn fs_write_bytes(context: &mut Context, pos: &Position) -> Result<V, E> {
+    let path = arg_str(context, "path");
+    let data = match context.get(&String::from("data")) {
+        Some(V::Bytes(data)) => data.clone(),
+        _ => vec![]
+    };
+    match fs::write(&path, data) {
+        Ok(()) => Ok(V::Null),
+        Err(_) => {
+            context.trace(pos.clone());
+            Err(E::FileWrite(path))
+        }
+    }
+}
+/// the rules defined for every script
+pub const PRELUDE: &str = include_str!("prelude.pr");
+
+/// runs the prelude in the context, its definitions keep `<PRELUDE>` as their path
+fn load_prelude(context: &mut Context) {
+    let (path, text) = (String::from("<PRELUDE>"), String::from(PRELUDE));
+    let script_path = std::mem::replace(&mut context.path, path.clone());
+    let res = lex(&path, &text, context)
+        .and_then(|tokens| parse(&path, &text, tokens, context))
+        .and_then(|ast| interpret(&ast, context));
+    context.path = script_path;
+    if let Err(e) = res {
+        panic!("the prelude is invalid\n{e}\n{}", display_trace(context.trace.clone()))
+    }
+}
 pub fn std_context(context: &mut Context) {
     fn type_node(typ: Type) -> Node {
         Node(N::Type(typ), Position::new(0..0, 0..0))
     }
     context.def(&String::from("print"), &V::ForeignProc(vec![
-        ("x".into(), None, false)
+        ("x".into(), None, false, false)
     ], _print));
     context.def(&String::from("input"), &V::ForeignProc(vec![
-        ("x".into(), Some(type_node(Type::String)), false)
+        ("x".into(), Some(type_node(Type::String)), false, false)
     ], _input));
     context.def(&String::from("assert"), &V::ForeignProc(vec![
-        ("x".into(), None, false)
+        ("x".into(), None, false, false)
     ], _assert));
     // str
     let mut str_context = Context::new(&String::from("<STR>"));
     str_context.def(&String::from("join"), &V::ForeignProc(vec![
-        ("self".into(), Some(type_node(Type::String)), false),
-        ("list".into(), Some(type_node(Type::Vector(vec![Type::Any]))), false)
+        ("self".into(), Some(type_node(Type::String)), false, false),
+        ("list".into(), Some(type_node(Type::Vector(vec![Type::Any]))), false, false)
     ], str_join));
+    str_context.def(&String::from("split"), &V::ForeignProc(vec![
+        ("self".into(), Some(type_node(Type::String)), false, false),
+        ("sep".into(), Some(type_node(Type::create_union(vec![Type::String, Type::Undefined]))), false, false)
+    ], str_split));
+    str_context.def(&String::from("replace"), &V::ForeignProc(vec![
+        ("self".into(), Some(type_node(Type::String)), false, false),
+        ("from".into(), Some(type_node(Type::String)), false, false),
+        ("to".into(), Some(type_node(Type::String)), false, false)
+    ], str_replace));
+    str_context.def(&String::from("starts_with"), &V::ForeignProc(vec![
+        ("self".into(), Some(type_node(Type::String)), false, false),
+        ("prefix".into(), Some(type_node(Type::String)), false, false)
+    ], str_starts_with));
+    str_context.def(&String::from("ends_with"), &V::ForeignProc(vec![
+        ("self".into(), Some(type_node(Type::String)), false, false),
+        ("suffix".into(), Some(type_node(Type::String)), false, false)
+    ], str_ends_with));
+    str_context.def(&String::from("find"), &V::ForeignProc(vec![
+        ("self".into(), Some(type_node(Type::String)), false, false),
+        ("sub".into(), Some(type_node(Type::String)), false, false)
+    ], str_find));
+    for (name, func) in [
+        ("trim", str_trim as ProcFn), ("upper", str_upper), ("lower", str_lower), ("chars", str_chars)
+    ] {
+        str_context.def(&String::from(name), &V::ForeignProc(vec![
+            ("self".into(), Some(type_node(Type::String)), false, false)
+        ], func));
+    }
+    str_context.def(&String::from("repeat"), &V::ForeignProc(vec![
+        ("self".into(), Some(type_node(Type::String)), false, false),
+        ("n".into(), Some(type_node(Type::Int)), false, false)
+    ], str_repeat));
+    str_context.def(&String::from("slice"), &V::ForeignProc(vec![
+        ("self".into(), Some(type_node(Type::String)), false, false),
+        ("start".into(), Some(type_node(Type::Int)), false, false),
+        ("end".into(), Some(type_node(Type::create_union(vec![Type::Int, Type::Undefined]))), false, false)
+    ], str_slice));
+    for (name, func) in [("pad_left", str_pad_left as ProcFn), ("pad_right", str_pad_right)] {
+        str_context.def(&String::from(name), &V::ForeignProc(vec![
+            ("self".into(), Some(type_node(Type::String)), false, false),
+            ("width".into(), Some(type_node(Type::Int)), false, false),
+            ("fill".into(), Some(type_node(Type::create_union(vec![Type::String, Type::Undefined]))), false, false)
+        ], func));
+    }
+    str_context.def(&String::from("encode"), &V::ForeignProc(vec![
+        ("self".into(), Some(type_node(Type::String)), false, false)
+    ], str_encode));
+    str_context.def(&String::from("format"), &V::ForeignProc(vec![
+        ("template".into(), Some(type_node(Type::String)), false, false),
+        ("args".into(), None, false, true)
+    ], str_format));
     context.def(&String::from("str"), &&V::Container(str_context));
+    // bytes
+    let mut bytes_context = Context::new(&String::from("<BYTES>"));
+    for (name, func) in [("decode", bytes_decode as ProcFn), ("hex", bytes_hex), ("base64", bytes_base64)] {
+        bytes_context.def(&String::from(name), &V::ForeignProc(vec![
+            ("self".into(), Some(type_node(Type::Bytes)), false, false)
+        ], func));
+    }
+    for (name, func) in [("from_hex", bytes_from_hex as ProcFn), ("from_base64", bytes_from_base64)] {
+        bytes_context.def(&String::from(name), &V::ForeignProc(vec![
+            ("s".into(), Some(type_node(Type::String)), false, false)
+        ], func));
+    }
+    bytes_context.def(&String::from("slice"), &V::ForeignProc(vec![
+        ("self".into(), Some(type_node(Type::Bytes)), false, false),
+        ("start".into(), Some(type_node(Type::Int)), false, false),
+        ("end".into(), Some(type_node(Type::create_union(vec![Type::Int, Type::Undefined]))), false, false)
+    ], bytes_slice));
+    context.def(&String::from("bytes"), &&V::Container(bytes_context));
+    // vec
+    let mut vec_context = Context::new(&String::from("<VEC>"));
+    for (name, func) in [("map", vec_map as ProcFn), ("filter", vec_filter)] {
+        vec_context.def(&String::from(name), &V::ForeignProc(vec![
+            ("self".into(), Some(type_node(Type::Vector(vec![Type::Any]))), false, false),
+            ("f".into(), Some(type_node(callable_type(false))), false, false)
+        ], func));
+    }
+    vec_context.def(&String::from("reduce"), &V::ForeignProc(vec![
+        ("self".into(), Some(type_node(Type::Vector(vec![Type::Any]))), false, false),
+        ("f".into(), Some(type_node(callable_type(false))), false, false),
+        ("init".into(), None, false, false)
+    ], vec_reduce));
+    for (name, func) in [("any", vec_any as ProcFn), ("all", vec_all)] {
+        vec_context.def(&String::from(name), &V::ForeignProc(vec![
+            ("self".into(), Some(type_node(Type::Vector(vec![Type::Any]))), false, false),
+            ("f".into(), Some(type_node(callable_type(true))), false, false)
+        ], func));
+    }
+    vec_context.def(&String::from("sort"), &V::ForeignProc(vec![
+        ("self".into(), Some(type_node(Type::Vector(vec![Type::Any]))), false, false),
+        ("key".into(), Some(type_node(callable_type(true))), false, false)
+    ], vec_sort));
+    for (name, func) in [("reverse", vec_reverse as ProcFn), ("enumerate", vec_enumerate), ("sum", vec_sum)] {
+        vec_context.def(&String::from(name), &V::ForeignProc(vec![
+            ("self".into(), Some(type_node(Type::Vector(vec![Type::Any]))), false, false)
+        ], func));
+    }
+    vec_context.def(&String::from("zip"), &V::ForeignProc(vec![
+        ("self".into(), Some(type_node(Type::Vector(vec![Type::Any]))), false, false),
+        ("other".into(), Some(type_node(Type::Vector(vec![Type::Any]))), false, false)
+    ], vec_zip));
+    for (name, func) in [("contains", vec_contains as ProcFn), ("index_of", vec_index_of)] {
+        vec_context.def(&String::from(name), &V::ForeignProc(vec![
+            ("self".into(), Some(type_node(Type::Vector(vec![Type::Any]))), false, false),
+            ("x".into(), None, false, false)
+        ], func));
+    }
+    context.def(&String::from("vec"), &&V::Container(vec_context));
+    // obj
+    let mut obj_context = Context::new(&String::from("<OBJ>"));
+    for (name, func) in [("keys", obj_keys as ProcFn), ("values", obj_values), ("entries", obj_entries)] {
+        obj_context.def(&String::from(name), &V::ForeignProc(vec![
+            ("self".into(), Some(type_node(Type::Object(vec![Type::Any]))), false, false)
+        ], func));
+    }
+    for (name, func) in [("has", obj_has as ProcFn), ("remove", obj_remove)] {
+        obj_context.def(&String::from(name), &V::ForeignProc(vec![
+            ("self".into(), Some(type_node(Type::Object(vec![Type::Any]))), false, false),
+            ("key".into(), Some(type_node(Type::String)), false, false)
+        ], func));
+    }
+    obj_context.def(&String::from("merge"), &V::ForeignProc(vec![
+        ("self".into(), Some(type_node(Type::Object(vec![Type::Any]))), false, false),
+        ("other".into(), Some(type_node(Type::Object(vec![Type::Any]))), false, false)
+    ], obj_merge));
+    obj_context.def(&String::from("get"), &V::ForeignProc(vec![
+        ("self".into(), Some(type_node(Type::Object(vec![Type::Any]))), false, false),
+        ("key".into(), Some(type_node(Type::String)), false, false),
+        ("default".into(), None, false, false)
+    ], obj_get));
+    context.def(&String::from("obj"), &&V::Container(obj_context));
     // fs
     let mut fs_context = Context::new(&String::from("<FS>"));
     fs_context.def(&String::from("read"), &V::ForeignProc(vec![
-        ("path".into(), Some(type_node(Type::String)), false)
+        ("path".into(), Some(type_node(Type::String)), false, false)
     ], fs_read));
+    fs_context.def(&String::from("read_bytes"), &V::ForeignProc(vec![
+        ("path".into(), Some(type_node(Type::String)), false, false)
+    ], fs_read_bytes));
+    fs_context.def(&String::from("write_bytes"), &V::ForeignProc(vec![
+        ("path".into(), Some(type_node(Type::String)), false, false),
+        ("data".into(), Some(type_node(Type::Bytes)), false, false)
+    ], fs_write_bytes));
     context.def(&String::from("fs"), &&V::Container(fs_context));
+    // json
+    let mut json_context = Context::new(&String::from("<JSON>"));
+    json_context.def(&String::from("parse"), &V::ForeignProc(vec![
+        ("s".into(), Some(type_node(Type::String)), false, false)
+    ], json_parse));
+    json_context.def(&String::from("stringify"), &V::ForeignProc(vec![
+        ("v".into(), None, false, false),
+        ("pretty".into(), Some(type_node(Type::create_union(vec![Type::Bool, Type::Undefined]))), false, false)
+    ], json_stringify));
+    context.def(&String::from("json"), &&V::Container(json_context));
+    // math
+    let mut math_context = Context::new(&String::from("<MATH>"));
+    for (name, v) in [("pi", std::f64::consts::PI), ("e", std::f64::consts::E), ("inf", f64::INFINITY), ("nan", f64::NAN)] {
+        math_context.def(&String::from(name), &V::Float(v));
+    }
+    for (name, func) in [
+        ("abs", math_abs as ProcFn), ("floor", math_floor), ("ceil", math_ceil), ("round", math_round),
+        ("sqrt", math_sqrt), ("exp", math_exp), ("sin", math_sin), ("cos", math_cos), ("tan", math_tan),
+        ("asin", math_asin), ("acos", math_acos), ("atan", math_atan)
+    ] {
+        math_context.def(&String::from(name), &V::ForeignProc(vec![
+            ("x".into(), Some(type_node(number_type())), false, false)
+        ], func));
+    }
+    math_context.def(&String::from("log"), &V::ForeignProc(vec![
+        ("x".into(), Some(type_node(number_type())), false, false),
+        ("base".into(), Some(type_node(Type::create_union(vec![number_type(), Type::Undefined]))), false, false)
+    ], math_log));
+    math_context.def(&String::from("atan2"), &V::ForeignProc(vec![
+        ("y".into(), Some(type_node(number_type())), false, false),
+        ("x".into(), Some(type_node(number_type())), false, false)
+    ], math_atan2));
+    math_context.def(&String::from("pow"), &V::ForeignProc(vec![
+        ("x".into(), Some(type_node(number_type())), false, false),
+        ("y".into(), Some(type_node(number_type())), false, false)
+    ], math_pow));
+    for (name, func) in [("min", math_min as ProcFn), ("max", math_max)] {
+        math_context.def(&String::from(name), &V::ForeignProc(vec![
+            ("values".into(), Some(type_node(Type::Vector(vec![Type::Any]))), false, false)
+        ], func));
+    }
+    math_context.def(&String::from("clamp"), &V::ForeignProc(vec![
+        ("x".into(), Some(type_node(number_type())), false, false),
+        ("lo".into(), Some(type_node(number_type())), false, false),
+        ("hi".into(), Some(type_node(number_type())), false, false)
+    ], math_clamp));
+    math_context.def(&String::from("div"), &V::ForeignProc(vec![
+        ("a".into(), Some(type_node(Type::create_union(vec![Type::Int, Type::BigInt]))), false, false),
+        ("b".into(), Some(type_node(Type::create_union(vec![Type::Int, Type::BigInt]))), false, false)
+    ], math_div));
+    context.def(&String::from("math"), &&V::Container(math_context));
+    // random
+    let mut random_context = Context::new(&String::from("<RANDOM>"));
+    random_context.def(&String::from("seed"), &V::ForeignProc(vec![
+        ("n".into(), Some(type_node(Type::Int)), false, false)
+    ], random_seed));
+    random_context.def(&String::from("int"), &V::ForeignProc(vec![
+        ("lo".into(), Some(type_node(Type::Int)), false, false),
+        ("hi".into(), Some(type_node(Type::Int)), false, false)
+    ], random_int));
+    random_context.def(&String::from("float"), &V::ForeignProc(vec![], random_float));
+    for (name, func) in [("choice", random_choice as ProcFn), ("shuffle", random_shuffle)] {
+        random_context.def(&String::from(name), &V::ForeignProc(vec![
+            ("values".into(), Some(type_node(Type::Vector(vec![Type::Any]))), false, false)
+        ], func));
+    }
+    context.def(&String::from("random"), &&V::Container(random_context));
+    // time
+    let mut time_context = Context::new(&String::from("<TIME>"));
+    for (name, func) in [("now", time_now as ProcFn), ("monotonic", time_monotonic)] {
+        time_context.def(&String::from(name), &V::ForeignProc(vec![], func));
+    }
+    time_context.def(&String::from("sleep"), &V::ForeignProc(vec![
+        ("seconds".into(), Some(type_node(Type::create_union(vec![Type::Int, Type::Float]))), false, false)
+    ], time_sleep));
+    for (name, func) in [("date", time_date as ProcFn), ("format", time_format)] {
+        time_context.def(&String::from(name), &V::ForeignProc(vec![
+            ("t".into(), Some(type_node(Type::create_union(vec![Type::Int, Type::Float]))), false, false)
+        ], func));
+    }
+    time_context.def(&String::from("timestamp"), &V::ForeignProc(vec![
+        ("date".into(), Some(type_node(Type::Object(vec![Type::Any]))), false, false)
+    ], time_timestamp));
+    time_context.def(&String::from("parse"), &V::ForeignProc(vec![
+        ("s".into(), Some(type_node(Type::String)), false, false)
+    ], time_parse));
+    context.def(&String::from("time"), &&V::Container(time_context));
+    // sys
+    let mut sys_context = Context::new(&String::from("<SYS>"));
+    sys_context.def(&String::from("args"), &V::Vector(vec![], vec![]));
+    sys_context.def(&String::from("env"), &V::ForeignProc(vec![
+        ("name".into(), Some(type_node(Type::String)), false, false)
+    ], sys_env));
+    sys_context.def(&String::from("set_env"), &V::ForeignProc(vec![
+        ("name".into(), Some(type_node(Type::String)), false, false),
+        ("value".into(), Some(type_node(Type::String)), false, false)
+    ], sys_set_env));
+    sys_context.def(&String::from("exit"), &V::ForeignProc(vec![
+        ("code".into(), Some(type_node(Type::create_union(vec![Type::Int, Type::Undefined]))), false, false)
+    ], sys_exit));
+    sys_context.def(&String::from("cwd"), &V::ForeignProc(vec![], sys_cwd));
+    sys_context.def(&String::from("run"), &V::ForeignProc(vec![
+        ("cmd".into(), Some(type_node(Type::String)), false, false),
+        ("args".into(), Some(type_node(Type::create_union(vec![Type::Vector(vec![Type::Any]), Type::Undefined]))), false, false)
+    ], sys_run));
+    context.def(&String::from("sys"), &&V::Container(sys_context));
+    // http
+    let mut http_context = Context::new(&String::from("<HTTP>"));
+    let headers_type = Type::create_union(vec![Type::Object(vec![Type::Any]), Type::Undefined]);
+    for (name, func) in [("get", http_get as ProcFn), ("delete", http_delete)] {
+        http_context.def(&String::from(name), &V::ForeignProc(vec![
+            ("url".into(), Some(type_node(Type::String)), false, false),
+            ("headers".into(), Some(type_node(headers_type.clone())), false, false)
+        ], func));
+    }
+    for (name, func) in [("post", http_post as ProcFn), ("put", http_put)] {
+        http_context.def(&String::from(name), &V::ForeignProc(vec![
+            ("url".into(), Some(type_node(Type::String)), false, false),
+            ("body".into(), Some(type_node(Type::create_union(vec![
+                Type::Object(vec![Type::Any]), Type::Vector(vec![Type::Any]), Type::String, Type::Undefined
+            ]))), false, false),
+            ("headers".into(), Some(type_node(headers_type.clone())), false, false)
+        ], func));
+    }
+    context.def(&String::from("http"), &&V::Container(http_context));
+    // net
+    let mut net_context = Context::new(&String::from("<NET>"));
+    for (name, func) in [("listen", net_listen as ProcFn), ("connect", net_connect), ("bind", net_bind)] {
+        net_context.def(&String::from(name), &V::ForeignProc(vec![
+            ("addr".into(), Some(type_node(Type::String)), false, false)
+        ], func));
+    }
+    for (name, func) in [("accept", net_accept as ProcFn), ("close", net_close), ("addr", net_addr)] {
+        net_context.def(&String::from(name), &V::ForeignProc(vec![
+            ("socket".into(), Some(type_node(Type::Socket)), false, false)
+        ], func));
+    }
+    net_context.def(&String::from("send"), &V::ForeignProc(vec![
+        ("socket".into(), Some(type_node(Type::Socket)), false, false),
+        ("data".into(), Some(type_node(Type::create_union(vec![Type::String, Type::Bytes]))), false, false)
+    ], net_send));
+    for (name, func) in [("recv", net_recv as ProcFn), ("recv_from", net_recv_from)] {
+        net_context.def(&String::from(name), &V::ForeignProc(vec![
+            ("socket".into(), Some(type_node(Type::Socket)), false, false),
+            ("max".into(), Some(type_node(Type::create_union(vec![Type::Int, Type::Undefined]))), false, false)
+        ], func));
+    }
+    net_context.def(&String::from("send_to"), &V::ForeignProc(vec![
+        ("socket".into(), Some(type_node(Type::Socket)), false, false),
+        ("data".into(), Some(type_node(Type::create_union(vec![Type::String, Type::Bytes]))), false, false),
+        ("addr".into(), Some(type_node(Type::String)), false, false)
+    ], net_send_to));
+    context.def(&String::from("net"), &&V::Container(net_context));
+    // prelude
+    load_prelude(context);
     // todo more std functions: io, fs, language primitivesss
 }
\ No newline at end of file
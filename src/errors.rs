@@ -1,23 +1,39 @@
 use std::{fs::read_to_string, error::Error};
 use crate::*;
 
+/// the clause of a rule that didn't hold and the value of the rule's identifier at that point
+#[derive(Clone, Debug)]
+pub struct RuleClause {
+    pub source: String,
+    pub pos: Position,
+    pub id: String,
+    pub value: V,
+}
+impl std::fmt::Display for RuleClause {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}: {} (where {} = {:?})", self.pos.0.start + 1, self.pos.1.start + 1, self.source, self.id, self.value)
+    }
+}
+
 #[derive(Clone, Debug)]
 pub enum E {
     Error(String),
     Todo(String),
     TargetFile(String), FileNotFound(String),
-    IllegalChar(String),
+    IllegalChar(String), UnterminatedString, InvalidEscape(String), InvalidNumber(String), NumberOverflow(String),
     UnexpectedToken(T), ExpectedToken(T, T),
-    ExpectedType(Type, Type), ExpectedTypeArg(String, Type, Type),
+    ExpectedType(Type, Type), ExpectedTypeArg(String, Type, Type), ExpectedElement(String, Type, Type),
     ExpectedNode(N, N),
-    Binary(T, V, V), InvalidBinaryOp(T),
+    Binary(T, V, V), InvalidBinaryOp(T), Overflow(T, V, V), DivisionByZero,
     Unary(T, V), InvalidUnaryOp(T),
     CannotAssign(N), NotDefined(String), AlreadyDefined(String),
-    Cast(Type, V), Nullable(Type),
+    Cast(Type, V), CastElement(String, Type, V), Nullable(Type),
     InvalidIterator(Type),
-    Rule(V, String), RuleCast(V, String),
-    InvalidField(Type, Type), InvalidHead(Type), FieldNotFound(String),
-    IndexRange(usize, i64),
+    Rule(V, Vec<String>, Option<Box<RuleClause>>), RuleCast(V, Vec<String>, Option<Box<RuleClause>>), RuleParams(String, usize, usize), Shape(String, Vec<String>),
+    InvalidField(Type, Type), InvalidHead(Type), FieldNotFound(String), KeyNotFound(V), Unhashable(Type),
+    IndexRange(usize, i64), EmptyRange(i64, i64), RangeStep,
+    JsonParse(String, usize, usize), Unserializable(V), Format(String), Date(String), Decode(String), FileWrite(String),
+    Process(String, String), Exit(i32), Http(String), Socket(String),
     Assertion, Test
 }
 impl std::fmt::Display for E {
@@ -28,12 +44,19 @@ impl std::fmt::Display for E {
             Self::FileNotFound(v) => write!(f, "ERROR: file '{v}' not found"),
             Self::Todo(v) => write!(f, "ERROR: todo - {v}"),
             Self::IllegalChar(c) => write!(f, "ERROR: illegal character {c:?}"),
+            Self::UnterminatedString => write!(f, "ERROR: unterminated string"),
+            Self::InvalidNumber(s) => write!(f, "ERROR: invalid number literal {s:?}"),
+            Self::NumberOverflow(s) => write!(f, "ERROR: number literal {s} is too large"),
+            Self::InvalidEscape(s) => write!(f, "ERROR: invalid escape sequence {s:?}"),
             Self::UnexpectedToken(t) => write!(f, "ERROR: unexpected {}", t.name()),
             Self::ExpectedToken(t1, t2) => write!(f, "ERROR: expected {}, got {}", t1.name(), t2.name()),
             Self::ExpectedType(t1, t2) => write!(f, "ERROR: expected {t1}, got {t2}"),
             Self::ExpectedTypeArg(arg, t1, t2) => write!(f, "ERROR: expected {t1} for #{arg} argument, got {t2}"),
+            Self::ExpectedElement(path, t1, t2) => write!(f, "ERROR: expected {t1} for element {path}, got {t2}"),
             Self::ExpectedNode(n1, n2) => write!(f, "ERROR: expected {}, got {}", n1.name(), n2.name()),
             Self::Binary(op, left, right) => write!(f, "ERROR: cannot perform {} on {} and {}", op.name(), left.typ(), right.typ()),
+            Self::Overflow(op, left, right) => write!(f, "ERROR: integer overflow in {left:?} {} {right:?}", op.name().trim_matches('\'')),
+            Self::DivisionByZero => write!(f, "ERROR: division by zero"),
             Self::InvalidBinaryOp(op) => write!(f, "ERROR: invalid binary operator {}", op.name()),
             Self::Unary(op, v) => write!(f, "ERROR: cannot perform {} on {}", op.name(), v.typ()),
             Self::InvalidUnaryOp(op) => write!(f, "ERROR: invalid unary operator {}", op.name()),
@@ -41,22 +64,60 @@ impl std::fmt::Display for E {
             Self::NotDefined(id) => write!(f, "ERROR: {id} is not defined"),
             Self::AlreadyDefined(id) => write!(f, "ERROR: {id} is already defined"),
             Self::Cast(typ, v) => write!(f, "ERROR: cannot cast {v:?} to {typ}"),
+            Self::CastElement(path, typ, v) => write!(f, "ERROR: cannot cast element {path} ({v:?}) to {typ}"),
             Self::Nullable(typ) => write!(f, "ERROR: cannot make {typ} nullable, only a type"),
             Self::InvalidIterator(typ) => write!(f, "ERROR: cannot iterate over {typ}"),
-            Self::Rule(v, name) => write!(f, "ERROR: rule {name} does not apply on {v:?}"),
-            Self::RuleCast(v, name) => write!(f, "ERROR: cannot cast {v:?} to {name}"),
+            Self::Rule(v, chain, clause) => {
+                write!(f, "ERROR: rule {} does not apply on {v:?}", chain.join(" -> "))?;
+                match clause {
+                    Some(clause) => write!(f, "\n    failed at {clause}"),
+                    None => Ok(())
+                }
+            }
+            Self::RuleCast(v, chain, clause) => {
+                write!(f, "ERROR: cannot cast {v:?} to {}", chain.join(" -> "))?;
+                match clause {
+                    Some(clause) => write!(f, "\n    failed at {clause}"),
+                    None => Ok(())
+                }
+            }
+            Self::RuleParams(name, expected, got) => write!(f, "ERROR: rule {name} expects {expected} parameters, got {got}"),
+            Self::Shape(name, failures) => write!(f, "ERROR: shape {name} does not apply\n{}",
+                failures.iter().map(|x| format!("    {x}")).collect::<Vec<String>>().join("\n")),
             Self::InvalidField(head, field) => write!(f, "ERROR: cannot index {head} by {field}"),
             Self::InvalidHead(head) => write!(f, "ERROR: cannot index {head}"),
             Self::FieldNotFound(field) => write!(f, "ERROR: field {field} not found"),
+            Self::KeyNotFound(key) => write!(f, "ERROR: key {key:?} not found"),
+            Self::Unhashable(typ) => write!(f, "ERROR: {typ} cannot be a key of a set or map"),
             Self::IndexRange(max, index) => write!(f, "ERROR: index {index} out of range of {max}"),
+            Self::Process(cmd, e) => write!(f, "ERROR: could not run {cmd}: {e}"),
+            Self::Http(e) => write!(f, "ERROR: http request failed, {e}"),
+            Self::Socket(e) => write!(f, "ERROR: socket error, {e}"),
+            Self::Exit(code) => write!(f, "ERROR: exited with code {code}"),
+            Self::Decode(what) => write!(f, "ERROR: invalid {what} data"),
+            Self::FileWrite(path) => write!(f, "ERROR: cannot write to {path}"),
+            Self::Date(s) => write!(f, "ERROR: {s} is not a valid date"),
+            Self::EmptyRange(lo, hi) => write!(f, "ERROR: there are no values from {lo} to {hi}"),
+            Self::RangeStep => write!(f, "ERROR: the step of a range cannot be 0"),
+            Self::JsonParse(msg, ln, col) => write!(f, "ERROR: invalid json at {}:{} - {msg}", ln + 1, col + 1),
+            Self::Format(template) => write!(f, "ERROR: invalid format string {template:?}"),
+            Self::Unserializable(v) => write!(f, "ERROR: cannot serialize {} to json", v.typ()),
             Self::Assertion => write!(f, "ERROR: assertion failed"),
             Self::Test => write!(f, "ERROR: test proc not found"),
         }
     }
 }
 
+impl E {
+    /// errors which mean that a value doesn't satisfy a type, rule or shape
+    pub fn is_rule_failure(&self) -> bool {
+        matches!(self, Self::Rule(_, _, _) | Self::RuleCast(_, _, _) | Self::Cast(_, _) | Self::CastElement(_, _, _) | Self::ExpectedType(_, _) | Self::ExpectedElement(_, _, _) | Self::Shape(_, _))
+    }
+}
+
 pub fn get_line(pos: &Position, path: &String) -> String {
-    match read_to_string(path) {
+    let text = if path == "<PRELUDE>" { Ok(PRELUDE.to_string()) } else { read_to_string(path) };
+    match text {
         Ok(text) => match text.split("\n").collect::<Vec<&str>>().get(pos.0.start..pos.0.end) {
             Some(line) => line.join("\n"),
             None => match text.split("\n").collect::<Vec<&str>>().last() {
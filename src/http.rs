@@ -0,0 +1,153 @@
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::time::Duration;
+use crate::*;
+
+/// sends raw request bytes to a host and returns the raw response bytes
+pub trait Transport {
+    fn send(&mut self, host: &str, port: u16, request: &[u8]) -> std::io::Result<Vec<u8>>;
+}
+/// plain TCP, the connection is closed after every request
+pub struct TcpTransport {
+    pub timeout: Duration
+}
+impl Default for TcpTransport {
+    fn default() -> Self { Self { timeout: Duration::from_secs(30) } }
+}
+impl Transport for TcpTransport {
+    fn send(&mut self, host: &str, port: u16, request: &[u8]) -> std::io::Result<Vec<u8>> {
+        let mut stream = TcpStream::connect((host, port))?;
+        stream.set_read_timeout(Some(self.timeout))?;
+        stream.write_all(request)?;
+        let mut response: Vec<u8> = vec![];
+        stream.read_to_end(&mut response)?;
+        Ok(response)
+    }
+}
+
+/// the host, port and path of an `http://` url
+pub fn parse_url(url: &str) -> Result<(String, u16, String), E> {
+    let rest = match url.strip_prefix("http://") {
+        Some(rest) => rest,
+        None => return Err(E::Http(format!("unsupported url {url}, only http:// is supported")))
+    };
+    let (authority, path) = match rest.find(['/', '?']) {
+        Some(i) => (&rest[..i], rest[i..].to_string()),
+        None => (rest, String::new())
+    };
+    let path = if path.starts_with('/') { path } else { format!("/{path}") };
+    let (host, port) = match authority.rsplit_once(':') {
+        Some((host, port)) => match port.parse::<u16>() {
+            Ok(port) => (host, port),
+            Err(_) => return Err(E::Http(format!("invalid port in {url}")))
+        }
+        None => (authority, 80)
+    };
+    if host.is_empty() { return Err(E::Http(format!("missing host in {url}"))) }
+    Ok((host.to_string(), port, path))
+}
+/// a `str` body is sent as text and an `obj` or `vec` body as JSON
+fn encode_body(body: &V) -> Result<Option<(String, &'static str)>, E> {
+    match body {
+        V::Null => Ok(None),
+        V::String(s) => Ok(Some((s.clone(), "text/plain; charset=utf-8"))),
+        _ => Ok(Some((stringify_json(body, false, 0)?, "application/json")))
+    }
+}
+fn find(bytes: &[u8], pattern: &[u8]) -> Option<usize> {
+    bytes.windows(pattern.len()).position(|w| w == pattern)
+}
+/// joins the chunks of a `Transfer-Encoding: chunked` body
+fn dechunk(mut bytes: &[u8]) -> Result<Vec<u8>, E> {
+    let mut body: Vec<u8> = vec![];
+    loop {
+        let end = find(bytes, b"\r\n").ok_or(E::Http("malformed chunked body".into()))?;
+        let size = String::from_utf8_lossy(&bytes[..end]);
+        let size = usize::from_str_radix(size.split(';').next().unwrap_or("").trim(), 16)
+            .map_err(|_| E::Http("malformed chunked body".into()))?;
+        bytes = &bytes[end + 2..];
+        if size == 0 { return Ok(body) }
+        if bytes.len() < size { return Err(E::Http("truncated chunked body".into())) }
+        body.extend_from_slice(&bytes[..size]);
+        bytes = bytes.get(size + 2..).unwrap_or(&[]);
+    }
+}
+/// turns a raw response into `{ status, headers, body }`, header names are lowercase
+pub fn parse_response(response: &[u8]) -> Result<V, E> {
+    let head_end = find(response, b"\r\n\r\n").ok_or(E::Http("malformed response".into()))?;
+    let head = String::from_utf8_lossy(&response[..head_end]);
+    let mut lines = head.split("\r\n");
+    let status = lines.next().unwrap_or("").split(' ').nth(1).and_then(|s| s.parse::<i64>().ok())
+        .ok_or(E::Http("malformed status line".into()))?;
+    let mut headers: OrderedMap<String, V> = OrderedMap::new();
+    for line in lines {
+        if let Some((name, value)) = line.split_once(':') {
+            headers.insert(name.trim().to_lowercase(), V::String(value.trim().to_string()));
+        }
+    }
+    let rest = &response[head_end + 4..];
+    let body = if headers.get("transfer-encoding") == Some(&V::String("chunked".into())) {
+        dechunk(rest)?
+    } else if let Some(V::String(length)) = headers.get("content-length") {
+        let length = length.parse::<usize>().map_err(|_| E::Http("invalid content-length".into()))?;
+        rest[..length.min(rest.len())].to_vec()
+    } else {
+        rest.to_vec()
+    };
+    let mut result: OrderedMap<String, V> = OrderedMap::new();
+    result.insert("status".into(), V::Int(status));
+    result.insert("headers".into(), V::Object(headers));
+    result.insert("body".into(), V::String(String::from_utf8_lossy(&body).to_string()));
+    Ok(V::Object(result))
+}
+/// sends a request over the transport and returns the parsed response,
+/// header names and values can't contain line breaks
+pub fn request(transport: &mut dyn Transport, method: &str, url: &str, body: &V, headers: &OrderedMap<String, V>) -> Result<V, E> {
+    let (host, port, path) = parse_url(url)?;
+    let mut head = format!("{method} {path} HTTP/1.1\r\nHost: {host}\r\nConnection: close\r\n");
+    let has_header = |name: &str| headers.keys().any(|k| k.eq_ignore_ascii_case(name));
+    for (name, value) in headers.iter() {
+        let value = value.to_string();
+        if name.contains(['\r', '\n']) || value.contains(['\r', '\n']) {
+            return Err(E::Http(format!("line break in header {name:?}")))
+        }
+        head.push_str(format!("{name}: {value}\r\n").as_str());
+    }
+    let body = encode_body(body)?;
+    if let Some((body, content_type)) = &body {
+        if !has_header("content-type") { head.push_str(format!("Content-Type: {content_type}\r\n").as_str()) }
+        head.push_str(format!("Content-Length: {}\r\n", body.len()).as_str());
+    }
+    head.push_str("\r\n");
+    let mut bytes = head.into_bytes();
+    if let Some((body, _)) = body { bytes.extend_from_slice(body.as_bytes()) }
+    match transport.send(&host, port, &bytes) {
+        Ok(response) => parse_response(&response),
+        Err(e) => Err(E::Http(format!("{url}: {e}")))
+    }
+}
+
+fn http_call(context: &mut Context, pos: &Position, method: &str) -> Result<V, E> {
+    let url = match context.get(&String::from("url")) {
+        Some(V::String(url)) => url.clone(),
+        _ => String::new()
+    };
+    let body = context.get(&String::from("body")).cloned().unwrap_or(V::Null);
+    let headers = match context.get(&String::from("headers")) {
+        Some(V::Object(headers)) => headers.clone(),
+        _ => OrderedMap::new()
+    };
+    request(&mut TcpTransport::default(), method, &url, &body, &headers).inspect_err(|_| context.trace(pos.clone()))
+}
+pub fn http_get(context: &mut Context, pos: &Position) -> Result<V, E> {
+    http_call(context, pos, "GET")
+}
+pub fn http_post(context: &mut Context, pos: &Position) -> Result<V, E> {
+    http_call(context, pos, "POST")
+}
+pub fn http_put(context: &mut Context, pos: &Position) -> Result<V, E> {
+    http_call(context, pos, "PUT")
+}
+pub fn http_delete(context: &mut Context, pos: &Position) -> Result<V, E> {
+    http_call(context, pos, "DELETE")
+}
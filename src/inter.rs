@@ -1,15 +1,58 @@
-use std::collections::HashMap;
 use std::cmp::min;
 use crate::*;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum R { None, Return, Break, Continue }
 
+/// arithmetic and comparisons where one side is a `bigint`, the other side is promoted to it
+pub fn bigint_binary(op: &T, left: &V, right: &V, pos: &Position, context: &mut Context) -> Option<Result<V, E>> {
+    if !matches!(op, T::Add | T::Sub | T::Mul | T::Div | T::Mod | T::Pow | T::LT | T::GT | T::LE | T::GE) { return None }
+    let (v1, v2) = match (left, right) {
+        (V::BigInt(v1), V::BigInt(v2)) => (v1.clone(), v2.clone()),
+        (V::BigInt(v1), V::Int(v2)) => (v1.clone(), BigInt::from_i64(*v2)),
+        (V::Int(v1), V::BigInt(v2)) => (BigInt::from_i64(*v1), v2.clone()),
+        (V::BigInt(v1), V::Float(_)) => return Some(binary(op, &V::Float(v1.to_f64()), right, pos, context)),
+        (V::Float(_), V::BigInt(v2)) => return Some(binary(op, left, &V::Float(v2.to_f64()), pos, context)),
+        _ => return None
+    };
+    Some(Ok(match op {
+        T::Add => V::BigInt(v1.add(&v2)),
+        T::Sub => V::BigInt(v1.sub(&v2)),
+        T::Mul => V::BigInt(v1.mul(&v2)),
+        T::Div | T::Mod => match v1.div_rem(&v2) {
+            Some((quotient, rem)) => V::BigInt(if op == &T::Div { quotient } else { rem }),
+            None => {
+                context.trace(pos.to_owned());
+                return Some(Err(E::DivisionByZero))
+            }
+        }
+        T::Pow if v2 < BigInt::default() => V::Float(v1.to_f64().powf(v2.to_f64())),
+        T::Pow => match v2.to_i64().and_then(|v| u32::try_from(v).ok()) {
+            Some(exp) => V::BigInt(v1.pow(exp)),
+            None => {
+                context.trace(pos.to_owned());
+                return Some(Err(E::Overflow(op.clone(), left.clone(), right.clone())))
+            }
+        }
+        T::LT => V::Bool(v1 < v2),
+        T::GT => V::Bool(v1 > v2),
+        T::LE => V::Bool(v1 <= v2),
+        _ => V::Bool(v1 >= v2),
+    }))
+}
 pub fn binary(op: &T, left: &V, right: &V, pos: &Position, context: &mut Context) -> Result<V, E> {
+    if let Some(res) = bigint_binary(op, left, right, pos, context) { return res }
+    let overflow = |context: &mut Context| {
+        context.trace(pos.to_owned());
+        Err(E::Overflow(op.clone(), left.clone(), right.clone()))
+    };
     match op {
         T::Add => match left {
             V::Int(v1) => match right {
-                V::Int(v2) => return Ok(V::Int(*v1 + *v2)),
+                V::Int(v2) => return match v1.checked_add(*v2) {
+                    Some(v) => Ok(V::Int(v)),
+                    None => overflow(context)
+                },
                 V::Float(v2) => return Ok(V::Float(*v1 as f64 + v2)),
                 _ => {}
             }
@@ -22,11 +65,18 @@ pub fn binary(op: &T, left: &V, right: &V, pos: &Position, context: &mut Context
                 V::String(v2) => return Ok(V::String(v1.clone() + v2.as_str())),
                 _ => {}
             }
+            V::Bytes(v1) => match right {
+                V::Bytes(v2) => return Ok(V::Bytes([v1.as_slice(), v2.as_slice()].concat())),
+                _ => {}
+            }
             _ => {}
         }
         T::Sub => match left {
             V::Int(v1) => match right {
-                V::Int(v2) => return Ok(V::Int(*v1 - *v2)),
+                V::Int(v2) => return match v1.checked_sub(*v2) {
+                    Some(v) => Ok(V::Int(v)),
+                    None => overflow(context)
+                },
                 V::Float(v2) => return Ok(V::Float(*v1 as f64 - *v2)),
                 _ => {}
             }
@@ -35,11 +85,18 @@ pub fn binary(op: &T, left: &V, right: &V, pos: &Position, context: &mut Context
                 V::Float(v2) => return Ok(V::Float(*v1 - *v2)),
                 _ => {}
             }
+            V::Set(v1) => match right {
+                V::Set(v2) => return Ok(V::Set(v1.iter().filter(|(k, _)| !v2.contains_key(*k)).map(|(k, _)| (k.clone(), ())).collect())),
+                _ => {}
+            }
             _ => {}
         }
         T::Mul => match left {
             V::Int(v1) => match right {
-                V::Int(v2) => return Ok(V::Int(*v1 * *v2)),
+                V::Int(v2) => return match v1.checked_mul(*v2) {
+                    Some(v) => Ok(V::Int(v)),
+                    None => overflow(context)
+                },
                 V::Float(v2) => return Ok(V::Float(*v1 as f64 * *v2)),
                 _ => {}
             }
@@ -49,13 +106,17 @@ pub fn binary(op: &T, left: &V, right: &V, pos: &Position, context: &mut Context
                 _ => {}
             }
             V::String(v1) => match right {
-                V::Int(v2) => return Ok(V::String(v1.repeat(*v2 as usize))),
+                V::Int(v2) => return Ok(V::String(v1.repeat((*v2).max(0) as usize))),
                 _ => {}
             }
             _ => {}
         }
         T::Div => match left {
             V::Int(v1) => match right {
+                V::Int(0) => {
+                    context.trace(pos.to_owned());
+                    return Err(E::DivisionByZero)
+                }
                 V::Int(v2) => return Ok(V::Float(*v1 as f64 / *v2 as f64)),
                 V::Float(v2) => return Ok(V::Float(*v1 as f64 / *v2)),
                 _ => {}
@@ -69,7 +130,14 @@ pub fn binary(op: &T, left: &V, right: &V, pos: &Position, context: &mut Context
         }
         T::Mod => match left {
             V::Int(v1) => match right {
-                V::Int(v2) => return Ok(V::Int(*v1 % *v2)),
+                V::Int(0) => {
+                    context.trace(pos.to_owned());
+                    return Err(E::DivisionByZero)
+                }
+                V::Int(v2) => return match v1.checked_rem(*v2) {
+                    Some(v) => Ok(V::Int(v)),
+                    None => overflow(context)
+                },
                 V::Float(v2) => return Ok(V::Float(*v1 as f64 % *v2)),
                 _ => {}
             }
@@ -80,6 +148,23 @@ pub fn binary(op: &T, left: &V, right: &V, pos: &Position, context: &mut Context
             }
             _ => {}
         }
+        T::Pow => match left {
+            V::Int(v1) => match right {
+                V::Int(v2) if *v2 < 0 => return Ok(V::Float((*v1 as f64).powf(*v2 as f64))),
+                V::Int(v2) => return match u32::try_from(*v2).ok().and_then(|v2| v1.checked_pow(v2)) {
+                    Some(v) => Ok(V::Int(v)),
+                    None => overflow(context)
+                },
+                V::Float(v2) => return Ok(V::Float((*v1 as f64).powf(*v2))),
+                _ => {}
+            }
+            V::Float(v1) => match right {
+                V::Int(v2) => return Ok(V::Float(v1.powf(*v2 as f64))),
+                V::Float(v2) => return Ok(V::Float(v1.powf(*v2))),
+                _ => {}
+            }
+            _ => {}
+        }
         T::EQ => return Ok(V::Bool(left == right)),
         T::NE => return Ok(V::Bool(left != right)),
         T::LT => match left {
@@ -135,19 +220,53 @@ pub fn binary(op: &T, left: &V, right: &V, pos: &Position, context: &mut Context
             _ => {}
         }
         T::Is => match right {
-            V::Type(typ) => return Ok(V::Bool(&left.typ() == typ)),
-            V::Rule(_, _, _) => return Ok(V::Bool(check_rule(right, left, pos, context).is_ok())),
+            V::Type(typ) => return Ok(V::Bool(typ.check(left).is_ok())),
+            V::Tuple(_) => if let V::Type(typ) = right.clone().into_type() {
+                return Ok(V::Bool(typ.check(left).is_ok()))
+            }
+            V::Rule(_, _, _, _, _) | V::RuleOp(_, _) => {
+                let trace_len = context.trace.len();
+                let res = check_rule(right, left, pos, context);
+                context.trace.truncate(trace_len);
+                return Ok(V::Bool(res.is_ok()))
+            }
+            V::Shape(name, _) => {
+                let trace_len = context.trace.len();
+                let res = check_shape(right, left, name, pos, context);
+                context.trace.truncate(trace_len);
+                return Ok(V::Bool(res.is_ok()))
+            }
             _ => {}
         }
         T::Contains => match right {
             V::Vector(v, t) => return Ok(V::Bool(v.contains(left))),
             V::Tuple(v) => return Ok(V::Bool(v.contains(left))),
+            V::Bytes(v) => match left {
+                V::Int(b) => return Ok(V::Bool(u8::try_from(*b).map(|b| v.contains(&b)).unwrap_or(false))),
+                V::Bytes(sub) => return Ok(V::Bool(sub.is_empty() || v.windows(sub.len()).any(|w| w == sub.as_slice()))),
+                _ => {}
+            }
             V::Object(v) => match left {
                 V::String(k) => return Ok(V::Bool(v.clone().into_keys().collect::<Vec<String>>().contains(k))),
                 _ => {}
             }
+            V::Set(v) => return Ok(V::Bool(Key::from(left).is_some_and(|k| v.contains_key(&k)))),
+            V::Map(v) => return Ok(V::Bool(Key::from(left).is_some_and(|k| v.contains_key(&k)))),
+            V::Range(start, end, step) => match left {
+                V::Int(x) => {
+                    let offset = *x as i128 - *start as i128;
+                    let n = offset / *step as i128;
+                    return Ok(V::Bool(offset % *step as i128 == 0 && n >= 0 && n < range_len(*start, *end, *step)))
+                }
+                _ => {}
+            }
             _ => {}
         }
+        T::Or if left.is_constraint() && right.is_constraint() => match (left, right) {
+            (V::Type(typ1), V::Type(typ2)) => return Ok(V::Type(Type::create_union(vec![typ1.clone(), typ2.clone()]))),
+            _ => return Ok(V::rule_op(T::Or, left, right))
+        }
+        T::And if left.is_constraint() && right.is_constraint() => return Ok(V::rule_op(T::And, left, right)),
         T::Or => if V::bool(left) == V::Bool(true) {
             return Ok(left.clone())
         } else {
@@ -168,6 +287,33 @@ pub fn binary(op: &T, left: &V, right: &V, pos: &Position, context: &mut Context
         }
         T::Option => match (left, right) {
             (V::Type(typ1), V::Type(typ2)) => return Ok(V::Type(Type::create_union(vec![typ1.clone(), typ2.clone()]))),
+            (V::Tuple(_), _) | (_, V::Tuple(_)) => if let (V::Type(typ1), V::Type(typ2)) = (left.clone().into_type(), right.clone().into_type()) {
+                return Ok(V::Type(Type::create_union(vec![typ1, typ2])))
+            }
+            (V::Int(v1), V::Int(v2)) => return Ok(V::Int(v1 | v2)),
+            (V::Set(v1), V::Set(v2)) => {
+                let mut union = v1.clone();
+                union.extend(v2.iter().map(|(k, _)| (k.clone(), ())));
+                return Ok(V::Set(union))
+            }
+            _ => {}
+        }
+        T::BitAnd => match (left, right) {
+            (V::Int(v1), V::Int(v2)) => return Ok(V::Int(v1 & v2)),
+            (V::Set(v1), V::Set(v2)) => return Ok(V::Set(v1.iter().filter(|(k, _)| v2.contains_key(*k)).map(|(k, _)| (k.clone(), ())).collect())),
+            _ => {}
+        }
+        T::Xor => match (left, right) {
+            (V::Int(v1), V::Int(v2)) => return Ok(V::Int(v1 ^ v2)),
+            (V::Bool(v1), V::Bool(v2)) => return Ok(V::Bool(v1 != v2)),
+            _ => {}
+        }
+        // bits shifted out are dropped, `>>` keeps the sign
+        T::Shl | T::Shr => match (left, right) {
+            (V::Int(v1), V::Int(v2)) => return match u32::try_from(*v2).ok().filter(|v2| *v2 < 64) {
+                Some(v2) => Ok(V::Int(if op == &T::Shl { v1 << v2 } else { v1 >> v2 })),
+                None => overflow(context)
+            },
             _ => {}
         }
         _ => {
@@ -181,17 +327,41 @@ pub fn binary(op: &T, left: &V, right: &V, pos: &Position, context: &mut Context
 pub fn unary(op: &T, value: &V, pos: &Position, context: &mut Context) -> Result<V, E> {
     match op {
         T::Sub => match value {
-            V::Int(v) => return Ok(V::Int(-v)),
+            V::Int(v) => return match v.checked_neg() {
+                Some(v) => Ok(V::Int(v)),
+                None => {
+                    context.trace(pos.clone());
+                    Err(E::Overflow(T::Sub, V::Int(0), value.clone()))
+                }
+            },
+            V::BigInt(v) => return Ok(V::BigInt(v.neg())),
             V::Float(v) => return Ok(V::Float(-v)),
             _ => {}
         }
         T::Len => match value {
-            V::String(v) => return Ok(V::Int(v.len() as i64)),
+            V::String(v) => return Ok(V::Int(v.chars().count() as i64)),
             V::Vector(v, _) => return Ok(V::Int(v.len() as i64)),
+            V::Tuple(v) => return Ok(V::Int(v.len() as i64)),
+            V::Bytes(v) => return Ok(V::Int(v.len() as i64)),
+            V::Set(v) => return Ok(V::Int(v.len() as i64)),
+            V::Map(v) => return Ok(V::Int(v.len() as i64)),
+            V::Range(start, end, step) => match i64::try_from(range_len(*start, *end, *step)) {
+                Ok(len) => return Ok(V::Int(len)),
+                Err(_) => {
+                    context.trace(pos.clone());
+                    return Err(E::Overflow(T::Sub, V::Int(*end), V::Int(*start)))
+                }
+            }
+            _ => {}
+        }
+        T::BitNot => match value {
+            V::Int(v) => return Ok(V::Int(!v)),
             _ => {}
         }
         T::Not => match value {
             V::Bool(v) => return Ok(V::Bool(!v)),
+            V::Type(typ) => return Ok(V::Type(Type::create_scission(vec![typ.clone()]))),
+            V::Rule(_, _, _, _, _) | V::RuleOp(_, _) | V::Shape(_, _) => return Ok(V::RuleOp(T::Not, vec![value.clone()])),
             _ => {}
         }
         T::Nullable => match value {
@@ -207,95 +377,380 @@ pub fn unary(op: &T, value: &V, pos: &Position, context: &mut Context) -> Result
     Err(E::Unary(op.clone(), value.clone()))
 }
 
-pub fn check_rule(rule_value: &V, value: &V, pos: &Position, context: &mut Context) -> Result<(), E> {
-    if let V::Rule(name, id, rules) = rule_value {
+pub fn bind_rule(rule_value: &V, value: &V, pos: &Position, context: &mut Context) -> Result<Context, E> {
+    if let V::Rule(name, params, _, args, path) = rule_value {
+        if args.len() + 1 != params.len() {
+            context.trace(pos.clone());
+            return Err(E::RuleParams(name.clone(), params.len() - 1, args.len()))
+        }
         let mut rule_context = Context::proc(context);
-        rule_context.set(id, value);
-        for (rule, _) in rules.iter() {
-            let (case, _) = interpret(rule, &mut rule_context)?;
-            if V::bool(&case) == V::Bool(false) {
-                context.trace(rule.1.clone());
-                return Err(E::Rule(value.clone(), name.clone()))
-            }
+        rule_context.path = path.clone();
+        rule_context.set(&params[0], value);
+        for (param, arg) in params[1..].iter().zip(args.iter()) {
+            rule_context.set(param, arg);
         }
-        return Ok(())
+        return Ok(rule_context)
     }
     context.trace(pos.clone());
     Err(E::ExpectedType(Type::Rule("<ANY>".into()), rule_value.typ()))
 }
-pub fn apply_rule(rule_value: &V, value: &V, pos: &Position, context: &mut Context) -> Result<V, E> {
-    if let V::Rule(name, id, rules) = rule_value {
-        let mut rule_context = Context::proc(context);
-        let mut new_value = value.clone();
-        rule_context.set(id, value);
-        for (rule, new) in rules.iter() {
-            let (case, _) = interpret(rule, &mut rule_context)?;
-            if V::bool(&case) == V::Bool(false) {
-                match new {
-                    Some(new) => {
-                        let res = interpret(new, &mut rule_context);
-                        match res {
-                            Ok((v, _)) => {
-                                rule_context.set(id, &v);
-                                new_value = v;
+/// evaluates a clause of a rule, a failing `x is other` clause also gives the failure of the nested rule
+fn rule_clause(rule: &Node, rule_context: &mut Context) -> Result<(bool, Option<E>), E> {
+    if let N::Binary { op: T::Is, left, right } = &rule.0 {
+        let (v, _) = interpret(left, rule_context)?;
+        let (constraint, _) = interpret(right, rule_context)?;
+        if let V::Rule(_, _, _, _, _) | V::RuleOp(_, _) = constraint {
+            let trace_len = rule_context.trace.len();
+            let res = check_rule(&constraint, &v, &rule.1, rule_context);
+            rule_context.trace.truncate(trace_len);
+            return Ok(match res {
+                Ok(()) => (true, None),
+                Err(e) => (false, Some(e))
+            })
+        }
+        let case = binary(&T::Is, &v, &constraint, &rule.1, rule_context)?;
+        return Ok((V::bool(&case) != V::Bool(false), None))
+    }
+    let (case, _) = interpret(rule, rule_context)?;
+    Ok((V::bool(&case) != V::Bool(false), None))
+}
+/// collects the chain of rules and the innermost clause that made a rule fail,
+/// continuing with the failure of the nested rule of an `x is other` clause
+pub fn rule_failure(name: &String, rule: &Node, source: &String, id: &String, nested: Option<E>, rule_context: &Context) -> (Vec<String>, Option<Box<RuleClause>>) {
+    let clause = Box::new(RuleClause {
+        source: source.clone(), pos: rule.1.clone(), id: id.clone(),
+        value: rule_context.get(id).cloned().unwrap_or(V::Null)
+    });
+    match nested {
+        Some(E::Rule(_, mut chain, inner)) => {
+            chain.insert(0, name.clone());
+            (chain, inner.or(Some(clause)))
+        }
+        _ => (vec![name.clone()], Some(clause))
+    }
+}
+pub fn check_rule(rule_value: &V, value: &V, pos: &Position, context: &mut Context) -> Result<(), E> {
+    match rule_value {
+        V::Rule(name, params, rules, _, path) => {
+            let mut rule_context = bind_rule(rule_value, value, pos, context)?;
+            for (rule, _, source) in rules.iter() {
+                let (case, nested) = rule_clause(rule, &mut rule_context)?;
+                if !case {
+                    let (chain, clause) = rule_failure(name, rule, source, &params[0], nested, &rule_context);
+                    context.trace.push((rule.1.clone(), path.clone()));
+                    return Err(E::Rule(value.clone(), chain, clause))
+                }
+            }
+            Ok(())
+        }
+        V::RuleOp(op, values) => {
+            let trace_len = context.trace.len();
+            match op {
+                T::And => {
+                    for v in values.iter() {
+                        check_constraint(v, value, pos, context)?;
+                    }
+                    Ok(())
+                }
+                T::Or => {
+                    for v in values.iter() {
+                        match check_constraint(v, value, pos, context) {
+                            Ok(()) => {
+                                context.trace.truncate(trace_len);
+                                return Ok(())
                             }
-                            Err(e) => {
-                                match e {
-                                    E::Cast(_, _) | E::RuleCast(_, _) => {
-                                        context.trace(rule.1.clone());
-                                        return Err(E::RuleCast(value.clone(), name.clone()))
+                            Err(e) if e.is_rule_failure() => {}
+                            Err(e) => return Err(e)
+                        }
+                    }
+                    context.trace.truncate(trace_len);
+                    context.trace(pos.clone());
+                    Err(E::Rule(value.clone(), vec![rule_value.typ().to_string()], None))
+                }
+                _ => match check_constraint(&values[0], value, pos, context) {
+                    Ok(()) => {
+                        context.trace(pos.clone());
+                        Err(E::Rule(value.clone(), vec![rule_value.typ().to_string()], None))
+                    }
+                    Err(e) if e.is_rule_failure() => {
+                        context.trace.truncate(trace_len);
+                        Ok(())
+                    }
+                    Err(e) => Err(e)
+                }
+            }
+        }
+        _ => {
+            context.trace(pos.clone());
+            Err(E::ExpectedType(Type::Rule("<ANY>".into()), rule_value.typ()))
+        }
+    }
+}
+pub fn apply_rule(rule_value: &V, value: &V, pos: &Position, context: &mut Context) -> Result<V, E> {
+    match rule_value {
+        V::Rule(name, params, rules, _, path) => {
+            let mut rule_context = bind_rule(rule_value, value, pos, context)?;
+            let id = &params[0];
+            let mut new_value = value.clone();
+            for (rule, new, source) in rules.iter() {
+                let (case, nested) = rule_clause(rule, &mut rule_context)?;
+                if !case {
+                    match new {
+                        Some(new) => {
+                            let res = interpret(new, &mut rule_context);
+                            match res {
+                                Ok((v, _)) => {
+                                    rule_context.set(id, &v);
+                                    new_value = v;
+                                }
+                                Err(e) => {
+                                    let clause = Box::new(RuleClause {
+                                        source: source.clone(), pos: rule.1.clone(), id: id.clone(),
+                                        value: rule_context.get(id).cloned().unwrap_or(V::Null)
+                                    });
+                                    match e {
+                                        E::Cast(_, _) => {
+                                            context.trace.push((rule.1.clone(), path.clone()));
+                                            return Err(E::RuleCast(value.clone(), vec![name.clone()], Some(clause)))
+                                        }
+                                        E::RuleCast(_, mut chain, inner) => {
+                                            context.trace.push((rule.1.clone(), path.clone()));
+                                            chain.insert(0, name.clone());
+                                            return Err(E::RuleCast(value.clone(), chain, inner.or(Some(clause))))
+                                        }
+                                        _ => return Err(e)
                                     }
-                                    _ => return Err(e)
                                 }
                             }
                         }
+                        None => {
+                            let (chain, clause) = rule_failure(name, rule, source, id, nested, &rule_context);
+                            context.trace.push((rule.1.clone(), path.clone()));
+                            return Err(E::RuleCast(value.clone(), chain, clause))
+                        }
                     }
-                    None => {
-                        context.trace(rule.1.clone());
-                        return Err(E::RuleCast(value.clone(), name.clone()))
+                }
+            }
+            Ok(new_value)
+        }
+        V::RuleOp(op, values) => match op {
+            T::And => {
+                let mut new_value = value.clone();
+                for v in values.iter() {
+                    new_value = apply_constraint(v, &new_value, pos, context)?;
+                }
+                Ok(new_value)
+            }
+            T::Or => {
+                let trace_len = context.trace.len();
+                for v in values.iter() {
+                    match apply_constraint(v, value, pos, context) {
+                        Ok(new_value) => {
+                            context.trace.truncate(trace_len);
+                            return Ok(new_value)
+                        }
+                        Err(e) if e.is_rule_failure() => {}
+                        Err(e) => return Err(e)
                     }
                 }
+                context.trace.truncate(trace_len);
+                context.trace(pos.clone());
+                Err(E::RuleCast(value.clone(), vec![rule_value.typ().to_string()], None))
+            }
+            _ => {
+                check_rule(rule_value, value, pos, context)?;
+                Ok(value.clone())
             }
         }
-        return Ok(new_value)
+        _ => {
+            context.trace(pos.clone());
+            Err(E::ExpectedType(Type::Rule("<ANY>".into()), rule_value.typ()))
+        }
+    }
+}
+/// checks a value against a type, rule or shape
+pub fn check_constraint(constraint: &V, value: &V, pos: &Position, context: &mut Context) -> Result<(), E> {
+    match constraint {
+        V::Type(typ) => match typ.check(value) {
+            Ok(()) => Ok(()),
+            Err(e) => {
+                context.trace(pos.clone());
+                Err(e)
+            }
+        }
+        V::Rule(_, _, _, _, _) | V::RuleOp(_, _) => check_rule(constraint, value, pos, context),
+        V::Shape(name, _) => check_shape(constraint, value, name, pos, context),
+        _ => {
+            context.trace(pos.clone());
+            Err(E::ExpectedType(Type::Union(vec![
+                Type::Type, Type::Rule("<ANY>".into()), Type::Shape("<ANY>".into())
+            ]), constraint.typ()))
+        }
+    }
+}
+/// like `check_constraint`, but casts the value to the type or applies the rule or shape
+pub fn apply_constraint(constraint: &V, value: &V, pos: &Position, context: &mut Context) -> Result<V, E> {
+    match constraint {
+        V::Type(typ) => match typ.cast(value) {
+            Some(v) => Ok(v),
+            None => {
+                context.trace(pos.clone());
+                Err(typ.cast_error(value))
+            }
+        }
+        V::Rule(_, _, _, _, _) | V::RuleOp(_, _) => apply_rule(constraint, value, pos, context),
+        V::Shape(name, _) => apply_shape(constraint, value, name, pos, context),
+        _ => {
+            context.trace(pos.clone());
+            Err(E::ExpectedType(Type::Union(vec![
+                Type::Type, Type::Rule("<ANY>".into()), Type::Shape("<ANY>".into())
+            ]), constraint.typ()))
+        }
+    }
+}
+
+pub fn match_shape(shape_value: &V, value: &V, path: &String, apply: bool, failures: &mut Vec<String>, pos: &Position, context: &mut Context) -> Result<V, E> {
+    if let V::Shape(_, fields) = shape_value {
+        let obj = match value {
+            V::Object(obj) => obj,
+            _ => {
+                failures.push(format!("{path}: expected obj, got {}", value.typ()));
+                return Ok(value.clone())
+            }
+        };
+        let mut new_obj = obj.clone();
+        for (field, constraint, optional) in fields.iter() {
+            let field_path = format!("{path}.{field}");
+            let field_value = match obj.get(field) {
+                Some(v) => v.clone(),
+                None => V::Null
+            };
+            if let V::Null = field_value {
+                if *optional { continue }
+                if !obj.contains_key(field) {
+                    failures.push(format!("{field_path}: missing field"));
+                    continue
+                }
+            }
+            match constraint {
+                V::Type(typ) => if apply {
+                    match typ.cast(&field_value) {
+                        Some(v) => { new_obj.insert(field.clone(), v); }
+                        None => if let Some((path, typ, v)) = typ.cast_mismatch(&field_value) {
+                            failures.push(format!("{field_path}{path}: cannot cast {} to {typ}", v.typ()))
+                        }
+                    }
+                } else if let Some((path, typ, v)) = typ.mismatch(&field_value) {
+                    failures.push(format!("{field_path}{path}: expected {typ}, got {}", v.typ()))
+                }
+                V::Rule(_, _, _, _, _) | V::RuleOp(_, _) => {
+                    let name = match constraint {
+                        V::Rule(name, _, _, _, _) => name.clone(),
+                        _ => constraint.typ().to_string()
+                    };
+                    let trace_len = context.trace.len();
+                    let res = if apply {
+                        apply_rule(constraint, &field_value, pos, context)
+                    } else {
+                        check_rule(constraint, &field_value, pos, context).map(|_| field_value.clone())
+                    };
+                    match res {
+                        Ok(v) => { new_obj.insert(field.clone(), v); }
+                        Err(E::Rule(_, _, _)) | Err(E::RuleCast(_, _, _)) => {
+                            context.trace.truncate(trace_len);
+                            failures.push(format!("{field_path}: rule {name} does not apply"))
+                        }
+                        Err(e) => return Err(e)
+                    }
+                }
+                V::Shape(_, _) => {
+                    let v = match_shape(constraint, &field_value, &field_path, apply, failures, pos, context)?;
+                    new_obj.insert(field.clone(), v);
+                }
+                _ => {
+                    context.trace(pos.clone());
+                    return Err(E::ExpectedType(Type::Union(vec![
+                        Type::Type, Type::Rule("<ANY>".into()), Type::Shape("<ANY>".into())
+                    ]), constraint.typ()))
+                }
+            }
+        }
+        return Ok(V::Object(new_obj))
     }
     context.trace(pos.clone());
-    Err(E::ExpectedType(Type::Rule("<ANY>".into()), rule_value.typ()))
+    Err(E::ExpectedType(Type::Shape("<ANY>".into()), shape_value.typ()))
+}
+pub fn check_shape(shape_value: &V, value: &V, path: &String, pos: &Position, context: &mut Context) -> Result<(), E> {
+    apply_shape_(shape_value, value, path, false, pos, context)?;
+    Ok(())
+}
+pub fn apply_shape(shape_value: &V, value: &V, path: &String, pos: &Position, context: &mut Context) -> Result<V, E> {
+    apply_shape_(shape_value, value, path, true, pos, context)
+}
+fn apply_shape_(shape_value: &V, value: &V, path: &String, apply: bool, pos: &Position, context: &mut Context) -> Result<V, E> {
+    let mut failures: Vec<String> = vec![];
+    let new_value = match_shape(shape_value, value, path, apply, &mut failures, pos, context)?;
+    if !failures.is_empty() {
+        context.trace(pos.clone());
+        return Err(E::Shape(shape_value.typ().name().to_string(), failures))
+    }
+    Ok(new_value)
 }
 
 pub fn assign_params(params: &ProcValueParams, arg_values: Vec<V>, poses: Vec<&Position>, context: &mut Context) -> Result<(), E> {
     for i in 0..params.len() {
-        let (param, type_node_, apply) = &params[i];
+        let (param, type_node_, apply, rest) = &params[i];
+        // a rest param collects the remaining arguments into a vector
+        if *rest {
+            let rest = arg_values.get(i..).unwrap_or_default().to_vec();
+            let types = V::create_union(rest.clone());
+            context.set(&param.to_string(), &V::Vector(rest, types));
+            break
+        }
         let mut value = match arg_values.get(i) {
             Some(v) => v.clone(),
             None => V::Null
         };
         if let Some(type_node) = type_node_ {
-            let (typ_, _) = interpret(type_node, context)?;
+            let typ_ = interpret(type_node, context)?.0.into_type();
             if let V::Type(typ) = typ_ {
-                if typ != value.typ() {
-                    if *apply {
+                if let Err(e) = typ.check(&value) {
+                    let e = if *apply {
                         match typ.cast(&value) {
-                            Some(new_value) => value = new_value,
-                            None => {
-                                context.trace(poses[i].clone());
-                                return Err(E::ExpectedTypeArg(format!("{i}"), typ.clone(), value.typ()))
+                            Some(new_value) => {
+                                value = new_value;
+                                None
                             }
+                            None => Some(typ.cast_error(&value))
                         }
                     } else {
+                        Some(e)
+                    };
+                    if let Some(e) = e {
                         context.trace(poses[i].clone());
-                        return Err(E::ExpectedTypeArg(format!("{i}"), typ.clone(), value.typ()))
+                        return Err(match e {
+                            E::ExpectedElement(_, _, _) | E::CastElement(_, _, _) => e,
+                            _ => E::ExpectedTypeArg(format!("{i}"), typ.clone(), value.typ())
+                        })
                     }
                 }
-            } else if let Type::Rule(_) = typ_.typ() {
+            } else if let V::Rule(_, _, _, _, _) | V::RuleOp(_, _) = typ_ {
                 if *apply {
                     value = apply_rule(&typ_, &value, &type_node.1, context)?;
                 } else {
                     check_rule(&typ_, &value, &type_node.1, context)?;
                 }
+            } else if let V::Shape(_, _) = typ_ {
+                if *apply {
+                    value = apply_shape(&typ_, &value, param, &type_node.1, context)?;
+                } else {
+                    check_shape(&typ_, &value, param, &type_node.1, context)?;
+                }
             } else {
                 context.trace(type_node.1.clone());
-                return Err(E::ExpectedType(Type::Union(vec![Type::Type, Type::Rule("<ANY>".into())]), typ_.typ()))
+                return Err(E::ExpectedType(Type::Union(vec![
+                    Type::Type, Type::Rule("<ANY>".into()), Type::Shape("<ANY>".into())
+                ]), typ_.typ()))
             }
         }
         context.set(param, &value);
@@ -318,76 +773,17 @@ pub fn get_field(head: &V, head_node: &Node, field_node: &Node, pos: &Position,
             context.trace(field_node.1.clone());
             Err(E::InvalidField(head.typ(), field.typ()))
         }
-        V::Vector(values, _) => if let Node(N::ID(field), field_pos) = field_node {
-            // todo
-            context.trace(field_pos.clone());
-            dbg!(&context.trace);
-            Err(E::FieldNotFound(field.clone()))
+        V::Vector(_, _) => if let Node(N::ID(field), field_pos) = field_node {
+            get_method(head, field, field_pos, head_node, context)
         } else {
             let (field, _) = interpret(field_node, context)?;
-            match field {
-                V::Int(index) => if index >= 0 {
-                    match values.get(index as usize) {
-                        Some(value) => Ok(value.clone()),
-                        None => {
-                            context.trace(field_node.1.clone());
-                            Err(E::IndexRange(values.len(), index))
-                        }
-                    }
-                } else {
-                    if values.len() as i64 - index >= 0 {
-                        match values.get((values.len() as i64 - index) as usize) {
-                            Some(value) => Ok(value.clone()),
-                            None => {
-                                context.trace(field_node.1.clone());
-                                Err(E::IndexRange(values.len(), index))
-                            }
-                        }
-                    } else {
-                        context.trace(field_node.1.clone());
-                        Err(E::IndexRange(values.len(), index))
-                    }
-                }
-                _ => {
-                    context.trace(field_node.1.clone());
-                    Err(E::InvalidField(head.typ(), field.typ()))
-                }
-            }
+            get_field_value(head, head_node, field, &field_node.1, pos, context)
         }
-        V::Tuple(values) => if let Node(N::ID(field), field_pos) = field_node {
-            // todo
-            context.trace(field_pos.clone());
-            Err(E::FieldNotFound(field.clone()))
+        V::Tuple(_) | V::Bytes(_) => if let Node(N::ID(field), field_pos) = field_node {
+            get_method(head, field, field_pos, head_node, context)
         } else {
             let (field, _) = interpret(field_node, context)?;
-            match field {
-                V::Int(index) => if index >= 0 {
-                    match values.get(index as usize) {
-                        Some(value) => Ok(value.clone()),
-                        None => {
-                            context.trace(field_node.1.clone());
-                            Err(E::IndexRange(values.len(), index))
-                        }
-                    }
-                } else {
-                    if values.len() as i64 - index >= 0 {
-                        match values.get((values.len() as i64 - index) as usize) {
-                            Some(value) => Ok(value.clone()),
-                            None => {
-                                context.trace(field_node.1.clone());
-                                Err(E::IndexRange(values.len(), index))
-                            }
-                        }
-                    } else {
-                        context.trace(field_node.1.clone());
-                        Err(E::IndexRange(values.len(), index))
-                    }
-                }
-                _ => {
-                    context.trace(field_node.1.clone());
-                    Err(E::InvalidField(head.typ(), field.typ()))
-                }
-            }
+            get_field_value(head, head_node, field, &field_node.1, pos, context)
         }
         V::Container(container_context) => if let Node(N::ID(field), field_pos) = field_node {
             match container_context.get(field) {
@@ -427,61 +823,235 @@ pub fn get_field(head: &V, head_node: &Node, field_node: &Node, pos: &Position,
             context.trace(field_node.1.clone());
             Err(E::InvalidField(head.typ(), field.typ()))
         }
+        _ => if let Node(N::ID(field), field_pos) = field_node {
+            get_method(head, field, field_pos, head_node, context)
+        } else {
+            context.trace(head_node.1.clone());
+            Err(E::InvalidHead(head.typ()))
+        }
+    }
+}
+/// looks up a field of a value in the std container named like its type, e.g. `split` of a `str` in `str`,
+/// only the global containers count so local variables can't replace methods
+pub fn get_method(head: &V, field: &String, field_pos: &Position, head_node: &Node, context: &mut Context) -> Result<V, E> {
+    match context.global.get(&head.typ().name().to_string()) {
+        Some(V::Container(type_context)) => match type_context.get(field) {
+            Some(value) => Ok(value.clone()),
+            None => {
+                context.trace(field_pos.clone());
+                Err(E::FieldNotFound(field.clone()))
+            }
+        }
         _ => {
             context.trace(head_node.1.clone());
             Err(E::InvalidHead(head.typ()))
         }
     }
 }
+/// evaluates what is called, for a method call like `s.split(",")` it also returns the head value,
+/// which is passed as the first argument (`self`)
+pub fn get_callee(id_node: &Node, context: &mut Context) -> Result<(V, Option<(V, Position)>), E> {
+    if let Node(N::Field { head: head_node, field: field_node }, pos) = id_node {
+        let (head, _) = interpret(head_node, context)?;
+        let value = get_field(&head, head_node, field_node, pos, context)?;
+        return match head {
+            V::Object(_) | V::Container(_) | V::Type(_) => Ok((value, None)),
+            _ => Ok((value, Some((head, head_node.1.clone()))))
+        }
+    }
+    let (value, _) = interpret(id_node, context)?;
+    Ok((value, None))
+}
+/// calls a `proc` or `foreign_proc` in a new scope and returns what it returns,
+/// `arg_poses` are where the arguments come from for the errors of their checks
+pub fn call_proc(proc: &V, arg_values: Vec<V>, arg_poses: Vec<&Position>, pos: &Position, context: &mut Context) -> Result<V, E> {
+    let old_context = context.clone();
+    *context = Context::proc(context);
+    let value = match proc {
+        V::Proc(params, body) => {
+            assign_params(params, arg_values, arg_poses, context)?;
+            context.trace(pos.clone());
+            interpret(body, context)?.0
+        }
+        V::ForeignProc(params, func) => {
+            assign_params(params, arg_values, arg_poses, context)?;
+            context.trace(pos.clone());
+            func(context, pos)?
+        }
+        _ => {
+            context.trace(pos.clone());
+            return Err(E::ExpectedType(Type::Union(vec![Type::Proc, Type::ForeignProc]), proc.typ()))
+        }
+    };
+    *context = old_context;
+    Ok(value)
+}
+/// the key of a value in a `set` or `map`
+pub fn key_of(value: &V, pos: &Position, context: &mut Context) -> Result<Key, E> {
+    match Key::from(value) {
+        Some(key) => Ok(key),
+        None => {
+            context.trace(pos.clone());
+            Err(E::Unhashable(value.typ()))
+        }
+    }
+}
+/// the value of a set or map literal, keys which can't be hashed are an error
+fn collection(node: &Node, context: &mut Context) -> Result<V, E> {
+    match node {
+        Node(N::Set(nodes), _) => {
+            let mut keys: OrderedMap<Key, ()> = OrderedMap::new();
+            for node in nodes.iter() {
+                let (v, _) = interpret(node, context)?;
+                keys.insert(key_of(&v, &node.1, context)?, ());
+            }
+            Ok(V::Set(keys))
+        }
+        Node(N::Map(nodes), _) => {
+            let mut entries: OrderedMap<Key, V> = OrderedMap::new();
+            for (key_node, node) in nodes.iter() {
+                let (k, _) = interpret(key_node, context)?;
+                let key = key_of(&k, &key_node.1, context)?;
+                let (v, _) = interpret(node, context)?;
+                entries.insert(key, v);
+            }
+            Ok(V::Map(entries))
+        }
+        _ => Ok(V::Null)
+    }
+}
+/// what a `for` loop goes through: the elements of a `vec` or tuple, the characters of a `str`,
+/// the bytes of `bytes` as `int`s, the numbers of a range, the values of a set
+/// and the entries of an `obj` or map as `(key, value)` tuples
+fn iter_values(value: V) -> Option<Box<dyn Iterator<Item = V>>> {
+    match value {
+        V::Vector(values, _) | V::Tuple(values) => Some(Box::new(values.into_iter())),
+        V::String(s) => Some(Box::new(s.chars().map(|c| V::String(c.to_string())).collect::<Vec<V>>().into_iter())),
+        V::Bytes(bytes) => Some(Box::new(bytes.into_iter().map(|b| V::Int(b as i64)))),
+        V::Range(start, end, step) => Some(Box::new(range_values(start, end, step).map(V::Int))),
+        V::Set(keys) => Some(Box::new(keys.into_keys().map(|k| k.value()))),
+        V::Map(entries) => Some(Box::new(entries.into_iter().map(|(k, v)| V::Tuple(vec![k.value(), v])))),
+        V::Object(fields) => Some(Box::new(fields.into_iter().map(|(k, v)| V::Tuple(vec![V::String(k), v])))),
+        _ => None
+    }
+}
+/// the value of `start..end..step`, an inclusive end becomes the next value after it
+fn range(node: &Node, context: &mut Context) -> Result<V, E> {
+    match node {
+        Node(N::Range { start, end, step: step_node, inclusive }, _) => {
+            let mut bounds: Vec<i64> = vec![];
+            for node in [Some(start), Some(end), step_node.as_ref()].into_iter().flatten() {
+                match interpret(node, context)?.0 {
+                    V::Int(v) => bounds.push(v),
+                    v => {
+                        context.trace(node.1.clone());
+                        return Err(E::ExpectedType(Type::Int, v.typ()))
+                    }
+                }
+            }
+            let step = bounds.get(2).copied().unwrap_or(1);
+            if step == 0 {
+                context.trace(step_node.as_ref().map_or(node.1.clone(), |step_node| step_node.1.clone()));
+                return Err(E::RangeStep)
+            }
+            if !*inclusive { return Ok(V::Range(bounds[0], bounds[1], step)) }
+            match bounds[1].checked_add(step.signum()) {
+                Some(end) => Ok(V::Range(bounds[0], end, step)),
+                None => {
+                    context.trace(end.1.clone());
+                    Err(E::Overflow(T::Add, V::Int(bounds[1]), V::Int(step.signum())))
+                }
+            }
+        }
+        _ => Ok(V::Null)
+    }
+}
+/// the values from `start` toward `end`, which is never reached
+pub fn range_values(start: i64, end: i64, step: i64) -> impl Iterator<Item = i64> {
+    std::iter::successors(Some(start), move |i| i.checked_add(step))
+        .take_while(move |i| if step > 0 { *i < end } else { *i > end })
+}
+/// the number of values of a range, which doesn't always fit in an `int`
+pub fn range_len(start: i64, end: i64, step: i64) -> i128 {
+    let span = if step > 0 { end as i128 - start as i128 } else { start as i128 - end as i128 };
+    let step = (step as i128).abs();
+    if span > 0 { (span + step - 1) / step } else { 0 }
+}
+/// the positions a range picks out of a sequence of `len` elements, its bounds may count from the end
+/// and are clamped to the sequence, so `[1..-1]` drops the first and the last element
+pub fn slice_indices(len: usize, start: i64, end: i64, step: i64) -> Vec<usize> {
+    let len = len as i64;
+    let (lo, hi) = if step > 0 { (0, len) } else { (-1, len - 1) };
+    let bound = |i: i64| (if i < 0 { len + i } else { i }).clamp(lo, hi);
+    range_values(bound(start), bound(end), step).map(|i| i as usize).collect()
+}
+/// turns an index which may count from the end (`-1` is the last) into a position in a sequence of `len` elements
+pub fn index_of(len: usize, index: i64) -> Option<usize> {
+    let i = if index < 0 { len as i64 + index } else { index };
+    if i >= 0 && (i as usize) < len { Some(i as usize) } else { None }
+}
 pub fn get_field_value(head: &V, head_node: &Node, field: V, field_pos: &Position, pos: &Position, context: &mut Context) -> Result<V, E> {
     match head {
-        V::Vector(values, _) => match field {
-            V::Int(index) => if index >= 0 {
-                match values.get(index as usize) {
-                    Some(value) => Ok(value.clone()),
-                    None => {
-                        context.trace(field_pos.clone());
-                        Err(E::IndexRange(values.len(), index))
-                    }
+        V::Vector(values, _) | V::Tuple(values) => match field {
+            V::Int(index) => match index_of(values.len(), index) {
+                Some(i) => Ok(values[i].clone()),
+                None => {
+                    context.trace(field_pos.clone());
+                    Err(E::IndexRange(values.len(), index))
                 }
-            } else if values.len() as i64 - index >= 0 {
-                match values.get((values.len() as i64 - index) as usize) {
-                    Some(value) => Ok(value.clone()),
-                    None => {
-                        context.trace(field_pos.clone());
-                        Err(E::IndexRange(values.len(), index))
-                    }
+            }
+            V::Range(start, end, step) => {
+                let values: Vec<V> = slice_indices(values.len(), start, end, step).into_iter().map(|i| values[i].clone()).collect();
+                match head {
+                    V::Tuple(_) => Ok(V::Tuple(values)),
+                    _ => Ok(V::Vector(values.clone(), V::create_union(values)))
                 }
-            } else {
+            }
+            _ => {
                 context.trace(field_pos.clone());
-                Err(E::IndexRange(values.len(), index))
+                Err(E::InvalidField(head.typ(), field.typ()))
+            }
+        }
+        V::Bytes(bytes) => match field {
+            V::Int(index) => match index_of(bytes.len(), index) {
+                Some(i) => Ok(V::Int(bytes[i] as i64)),
+                None => {
+                    context.trace(field_pos.clone());
+                    Err(E::IndexRange(bytes.len(), index))
+                }
             }
+            V::Range(start, end, step) => Ok(V::Bytes(slice_indices(bytes.len(), start, end, step).into_iter().map(|i| bytes[i]).collect())),
             _ => {
                 context.trace(field_pos.clone());
                 Err(E::InvalidField(head.typ(), field.typ()))
             }
         }
-        V::Tuple(values) => match field {
-            V::Int(index) => if index >= 0 {
-                match values.get(index as usize) {
-                    Some(value) => Ok(value.clone()),
+        V::String(s) => match field {
+            V::Int(index) => {
+                let len = s.chars().count();
+                match index_of(len, index) {
+                    Some(i) => Ok(V::String(s.chars().nth(i).unwrap().to_string())),
                     None => {
                         context.trace(field_pos.clone());
-                        Err(E::IndexRange(values.len(), index))
+                        Err(E::IndexRange(len, index))
                     }
                 }
-            } else {
-                if values.len() as i64 - index >= 0 {
-                    match values.get((values.len() as i64 - index) as usize) {
-                        Some(value) => Ok(value.clone()),
-                        None => {
-                            context.trace(field_pos.clone());
-                            Err(E::IndexRange(values.len(), index))
-                        }
-                    }
-                } else {
+            }
+            V::Range(start, end, step) => {
+                let chars: Vec<char> = s.chars().collect();
+                Ok(V::String(slice_indices(chars.len(), start, end, step).into_iter().map(|i| chars[i]).collect()))
+            }
+            _ => {
+                context.trace(field_pos.clone());
+                Err(E::InvalidField(head.typ(), field.typ()))
+            }
+        }
+        V::Object(obj) => match field {
+            V::String(key) => match obj.get(&key) {
+                Some(value) => Ok(value.clone()),
+                None => {
                     context.trace(field_pos.clone());
-                    Err(E::IndexRange(values.len(), index))
+                    Err(E::FieldNotFound(key))
                 }
             }
             _ => {
@@ -489,9 +1059,32 @@ pub fn get_field_value(head: &V, head_node: &Node, field: V, field_pos: &Positio
                 Err(E::InvalidField(head.typ(), field.typ()))
             }
         }
-        V::Type(head_type) => match field {
+        V::Map(entries) => match Key::from(&field) {
+            Some(key) => match entries.get(&key) {
+                Some(value) => Ok(value.clone()),
+                None => {
+                    context.trace(field_pos.clone());
+                    Err(E::KeyNotFound(field))
+                }
+            }
+            None => {
+                context.trace(field_pos.clone());
+                Err(E::Unhashable(field.typ()))
+            }
+        }
+        V::Type(head_type) => match field.clone().into_type() {
             V::Type(typ) => match head_type {
                 Type::Vector(_) => Ok(V::Type(typ.vector())),
+                Type::Object(_) => Ok(V::Type(typ.object())),
+                Type::Set(_) => Ok(V::Type(typ.set())),
+                // `map[str, int]` is indexed by the tuple type `(str, int)` of the keys and values
+                Type::Map(_) => match typ {
+                    Type::Tuple(types) if types.len() == 2 => Ok(V::Type(types[0].map(&types[1]))),
+                    _ => {
+                        context.trace(field_pos.clone());
+                        Err(E::InvalidField(head_type.clone(), typ))
+                    }
+                }
                 _ => {
                     context.trace(field_pos.clone());
                     Err(E::InvalidField(head_type.clone(), typ))
@@ -508,6 +1101,56 @@ pub fn get_field_value(head: &V, head_node: &Node, field: V, field_pos: &Positio
         }
     }
 }
+/// removes a variable, a field of an object like `del person.job` or `del scores[name]`,
+/// or a key of a map like `del counts[key]`
+pub fn delete(node: &Node, pos: &Position, context: &mut Context) -> Result<(), E> {
+    let (head_node, field, by_name) = match node {
+        Node(N::ID(id), id_pos) => {
+            if context.del(id).is_none() {
+                context.trace(id_pos.clone());
+                return Err(E::NotDefined(id.clone()))
+            }
+            return Ok(())
+        }
+        Node(N::Field { head: head_node, field: field_node }, _) => match field_node.as_ref() {
+            Node(N::ID(field), _) => (head_node, V::String(field.clone()), true),
+            _ => {
+                context.trace(field_node.1.clone());
+                return Err(E::ExpectedNode(N::ID("_".into()), field_node.0.clone()))
+            }
+        }
+        Node(N::FieldExpr { head: head_node, expr }, _) => (head_node, interpret(expr, context)?.0, false),
+        _ => {
+            context.trace(pos.clone());
+            return Err(E::CannotAssign(node.0.clone()))
+        }
+    };
+    let head = match head_node.as_ref() {
+        Node(N::ID(head), _) => head,
+        _ => {
+            context.trace(head_node.1.clone());
+            return Err(E::CannotAssign(head_node.0.clone()))
+        }
+    };
+    let res = match (context.get_mut(head), field) {
+        (Some(V::Object(obj)), V::String(key)) => match obj.remove(&key) {
+            Some(_) => Ok(()),
+            None => Err(E::FieldNotFound(key))
+        }
+        (Some(V::Object(_)), field) => Err(E::InvalidField(Type::Object(vec![Type::Any]), field.typ())),
+        (Some(V::Map(entries)), field) if !by_name => match Key::from(&field) {
+            Some(key) => match entries.remove(&key) {
+                Some(_) => Ok(()),
+                None => Err(E::KeyNotFound(field))
+            }
+            None => Err(E::Unhashable(field.typ()))
+        }
+        (Some(value), _) => Err(E::InvalidHead(value.typ())),
+        (None, _) => Err(E::NotDefined(head.clone()))
+    };
+    if res.is_err() { context.trace(pos.clone()) }
+    res
+}
 pub fn assign_new_value(new_value: V, id_node: &Node, pos: &Position, context: &mut Context) -> Result<(), E> {
     match id_node {
         Node(N::ID(id), id_pos) => {
@@ -560,34 +1203,43 @@ pub fn assign_new_value(new_value: V, id_node: &Node, pos: &Position, context: &
                         Some(old_head_value) => {
                             match old_head_value {
                                 V::Vector(values, typ) => match field {
-                                    V::Int(index) => if index >= 0 {
-                                        match values.get_mut(index as usize) {
-                                            Some(value) => {
-                                                *value = new_value;
-                                                Ok(())
-                                            }
-                                            None => {
-                                                Err(E::IndexRange(values.len(), index))
-                                            }
+                                    V::Int(index) => match index_of(values.len(), index) {
+                                        Some(i) => {
+                                            values[i] = new_value;
+                                            Ok(())
                                         }
-                                    } else if values.len() as i64 - index >= 0 {
-                                        let len = values.len();
-                                        match values.get_mut((len as i64 - index) as usize) {
-                                            Some(value) => {
-                                                *value = new_value;
-                                                Ok(())
-                                            }
-                                            None => {
-                                                Err(E::IndexRange(values.len(), index))
-                                            }
-                                        }
-                                    } else {
-                                        Err(E::IndexRange(values.len(), index))
+                                        None => Err(E::IndexRange(values.len(), index))
                                     }
                                     _ => {
                                         Err(E::InvalidField(Type::Vector(typ.clone()), field.typ()))
                                     }
                                 }
+                                V::Bytes(bytes) => match (field, &new_value) {
+                                    (V::Int(index), V::Int(b)) => match (index_of(bytes.len(), index), u8::try_from(*b)) {
+                                        (Some(i), Ok(b)) => {
+                                            bytes[i] = b;
+                                            Ok(())
+                                        }
+                                        (None, _) => Err(E::IndexRange(bytes.len(), index)),
+                                        (_, Err(_)) => Err(E::Cast(Type::Bytes, new_value.clone()))
+                                    }
+                                    (V::Int(_), _) => Err(E::ExpectedType(Type::Int, new_value.typ())),
+                                    (field, _) => Err(E::InvalidField(Type::Bytes, field.typ()))
+                                }
+                                V::Object(obj) => match field {
+                                    V::String(key) => {
+                                        obj.insert(key, new_value);
+                                        Ok(())
+                                    }
+                                    _ => Err(E::InvalidField(Type::Object(vec![Type::Any]), field.typ()))
+                                }
+                                V::Map(entries) => match Key::from(&field) {
+                                    Some(key) => {
+                                        entries.insert(key, new_value);
+                                        Ok(())
+                                    }
+                                    None => Err(E::Unhashable(field.typ()))
+                                }
                                 _ => {
                                     Err(E::InvalidHead(old_head_value.typ()))
                                 }
@@ -618,6 +1270,80 @@ pub fn assign_new_value(new_value: V, id_node: &Node, pos: &Position, context: &
     }
 }
 
+/// the new value of `old op= value`
+fn op_value(op: &T, old_value: &V, value: V, pos: &Position, context: &mut Context) -> Result<V, E> {
+    match op {
+        T::Assign => Ok(value),
+        T::AddAssign => binary(&T::Add, old_value, &value, pos, context),
+        T::SubAssign => binary(&T::Sub, old_value, &value, pos, context),
+        T::MulAssign => binary(&T::Mul, old_value, &value, pos, context),
+        T::DivAssign => binary(&T::Div, old_value, &value, pos, context),
+        T::ModAssign => binary(&T::Mod, old_value, &value, pos, context),
+        _ => Ok(old_value.clone())
+    }
+}
+/// `x op= value` for a variable, a field or an element, `x++` and `x--` add or subtract 1
+pub fn op_assign(op: &T, id_node: &Node, value: V, pos: &Position, context: &mut Context) -> Result<(), E> {
+    let new_value = match id_node {
+        Node(N::ID(id), id_pos) => match context.get(id).cloned() {
+            Some(old_value) => op_value(op, &old_value, value, pos, context),
+            None => {
+                context.trace(id_pos.clone());
+                Err(E::NotDefined(id.clone()))
+            }
+        }
+        Node(N::Field { head: head_node, field: field_node }, field_pos) =>
+            match head_node.as_ref() {
+                Node(N::ID(id), id_pos) => {
+                    let (head, _) = interpret(head_node, context)?;
+                    // `=` can add a new field to an object
+                    if *op == T::Assign && matches!(head, V::Object(_)) {
+                        assign_new_value(value, id_node, pos, context)?;
+                        return Ok(())
+                    }
+                    match get_field(&head, head_node, field_node, field_pos, context) {
+                        Ok(old_value) => op_value(op, &old_value, value, pos, context),
+                        Err(e) => {
+                            context.trace(id_pos.clone());
+                            Err(e)
+                        }
+                    }
+                }
+                _ => {
+                    context.trace(head_node.1.clone());
+                    Err(E::CannotAssign(head_node.0.clone()))
+                }
+            }
+        Node(N::FieldExpr { head: head_node, expr }, field_pos) => {
+            let (field_value, _) = interpret(expr, context)?;
+            match head_node.as_ref() {
+                Node(N::ID(id), id_pos) => {
+                    let (head, _) = interpret(head_node, context)?;
+                    if *op == T::Assign && matches!(head, V::Object(_) | V::Map(_)) {
+                        assign_new_value(value, id_node, pos, context)?;
+                        return Ok(())
+                    }
+                    match get_field_value(&head, head_node, field_value, field_pos, field_pos, context) {
+                        Ok(old_value) => op_value(op, &old_value, value, pos, context),
+                        Err(e) => {
+                            context.trace(id_pos.clone());
+                            Err(e)
+                        }
+                    }
+                }
+                _ => {
+                    context.trace(head_node.1.clone());
+                    Err(E::CannotAssign(head_node.0.clone()))
+                }
+            }
+        }
+        _ => {
+            context.trace(pos.clone());
+            Err(E::CannotAssign(id_node.0.clone()))
+        }
+    }?;
+    assign_new_value(new_value, id_node, pos, context)
+}
 pub fn interpret(input_node: &Node, context: &mut Context) -> Result<(V, R), E> {
     match input_node {
         // atom
@@ -627,6 +1353,15 @@ pub fn interpret(input_node: &Node, context: &mut Context) -> Result<(V, R), E>
         Node(N::Float(v), _) => Ok((V::Float(*v), R::None)),
         Node(N::Bool(v), _) => Ok((V::Bool(*v), R::None)),
         Node(N::String(v), _) => Ok((V::String(v.to_owned()), R::None)),
+        Node(N::Bytes(v), _) => Ok((V::Bytes(v.to_owned()), R::None)),
+        Node(N::Template(nodes), _) => {
+            let mut s = String::new();
+            for n in nodes.iter() {
+                let (v, _) = interpret(n, context)?;
+                if let Some(V::String(v)) = Type::String.cast(&v) { s.push_str(&v) }
+            }
+            Ok((V::String(s), R::None))
+        }
         Node(N::ID(id), pos) => {
             let value = context.get(id);
             match value {
@@ -658,18 +1393,22 @@ pub fn interpret(input_node: &Node, context: &mut Context) -> Result<(V, R), E>
             Ok((V::Tuple(values), R::None))
         }
         Node(N::Object(nodes), _) => {
-            let mut values: HashMap<String, V> = HashMap::new();
+            let mut values: OrderedMap<String, V> = OrderedMap::new();
             for (key_node, node) in nodes.iter() {
-                if let Node(N::ID(key_str), _) = key_node {
-                    let (v, _) = interpret(node, context)?;
-                    values.insert(key_str.clone(), v);
-                } else {
-                    context.trace(key_node.1.clone());
-                    return Err(E::ExpectedNode(N::ID("".into()), key_node.0.clone()))
-                }
+                let key = match interpret(key_node, context)?.0 {
+                    V::String(key) => key,
+                    key => {
+                        context.trace(key_node.1.clone());
+                        return Err(E::ExpectedType(Type::String, key.typ()))
+                    }
+                };
+                let (v, _) = interpret(node, context)?;
+                values.insert(key, v);
             }
             Ok((V::Object(values), R::None))
         }
+        Node(N::Set(_) | N::Map(_), _) => Ok((collection(input_node, context)?, R::None)),
+        Node(N::Range { .. }, _) => Ok((range(input_node, context)?, R::None)),
         Node(N::Type(v), _) => Ok((V::Type(v.to_owned()), R::None)),
 
         // operations
@@ -712,10 +1451,21 @@ pub fn interpret(input_node: &Node, context: &mut Context) -> Result<(V, R), E>
                             Ok((V::Bool(true), R::None))
                         }
                         T::Option => {
-                            let mut types: Vec<Type> = vec![];
+                            let mut values: Vec<V> = vec![];
                             for node in nodes.iter() {
-                                let (type_value, _) = interpret(node, context)?;
-                                if let V::Type(typ) = type_value {
+                                values.push(interpret(node, context)?.0);
+                            }
+                            // ints are or-ed together and sets joined, anything else has to be a type
+                            if let V::Int(_) | V::Set(_) = values[0] {
+                                let mut value = values[0].clone();
+                                for i in 1..values.len() {
+                                    value = binary(op, &value, &values[i], &nodes[i].1, context)?;
+                                }
+                                return Ok((value, R::None))
+                            }
+                            let mut types: Vec<Type> = vec![];
+                            for (type_value, node) in values.into_iter().zip(nodes.iter()) {
+                                if let V::Type(typ) = type_value.clone().into_type() {
                                     types.push(typ);
                                 } else {
                                     context.trace(node.1.clone());
@@ -726,9 +1476,9 @@ pub fn interpret(input_node: &Node, context: &mut Context) -> Result<(V, R), E>
                         }
                         _ => {
                             let (mut value, _) = interpret(&nodes[0], context)?;
-                            for i in 1..nodes.len() {
-                                let (v, _) = interpret(&nodes[i], context)?;
-                                value = binary(op, &value, &v, &nodes[i].1, context)?;
+                            for node in nodes.iter().skip(1) {
+                                let (v, _) = interpret(node, context)?;
+                                value = binary(op, &value, &v, &node.1, context)?;
                             }
                             Ok((value, R::None))
                         }
@@ -737,30 +1487,31 @@ pub fn interpret(input_node: &Node, context: &mut Context) -> Result<(V, R), E>
             }
         }
         Node(N::CallExpr { id: id_node, args }, pos) => {
-            let (proc, _) = interpret(id_node, context)?;
+            let (proc, self_value) = get_callee(id_node, context)?;
+            // a tuple of types like `(int, str)(x)` casts to the tuple type
+            let proc = proc.into_type();
             let mut arg_values: Vec<V> = vec![];
             let mut arg_poses: Vec<&Position> = vec![];
+            let self_pos;
+            if let Some((value, pos)) = self_value {
+                self_pos = pos;
+                arg_values.push(value);
+                arg_poses.push(&self_pos);
+            }
             for arg in args.iter() {
                 arg_poses.push(&arg.1);
                 let (value, _) = interpret(arg, context)?;
                 arg_values.push(value);
             }
+            if let V::Proc(_, _) | V::ForeignProc(_, _) = proc {
+                return Ok((call_proc(&proc, arg_values, arg_poses, pos, context)?, R::None))
+            }
             let old_context = context.clone();
             *context = Context::proc(context);
             let mut value = V::Null;
             match proc {
-                V::Proc(ref params, ref body) => {
-                    assign_params(params, arg_values, arg_poses, context)?;
-                    context.trace(pos.clone());
-                    (value, _) = interpret(body, context)?;
-                }
-                V::ForeignProc(ref params, ref func) => {
-                    assign_params(params, arg_values, arg_poses, context)?;
-                    context.trace(pos.clone());
-                    value = func(context, pos)?;
-                }
                 V::Type(typ) => {
-                    let arg = arg_values.get(0).unwrap_or_else(|| &V::Null);
+                    let arg = arg_values.first().unwrap_or(&V::Null);
                     match typ {
                         Type::Union(_) => {
                             let mut types: Vec<Type> = vec![];
@@ -790,19 +1541,33 @@ pub fn interpret(input_node: &Node, context: &mut Context) -> Result<(V, R), E>
                             Some(v) => value = v,
                             None => {
                                 context.trace(pos.clone());
-                                return Err(E::Cast(typ.clone(), arg.clone()))
+                                return Err(typ.cast_error(arg))
                             }
                         }
                     }
                 }
-                V::Rule(_, _, _) => {
-                    let arg = arg_values.get(0).unwrap_or_else(|| &V::Null);
+                V::Rule(ref name, ref params, ref rules, ref args, ref path) if params.len() > args.len() + 1 => {
+                    if params.len() != args.len() + arg_values.len() + 1 {
+                        context.trace(pos.clone());
+                        return Err(E::RuleParams(name.clone(), params.len() - args.len() - 1, arg_values.len()))
+                    }
+                    let name = format!("{name}({})", arg_values.iter().map(|x| format!("{x:?}")).collect::<Vec<String>>().join(", "));
+                    let mut args = args.clone();
+                    args.extend(arg_values);
+                    value = V::Rule(name, params.clone(), rules.clone(), args, path.clone());
+                }
+                V::Rule(_, _, _, _, _) | V::RuleOp(_, _) => {
+                    let arg = arg_values.first().unwrap_or(&V::Null);
                     value = apply_rule(&proc, arg, pos, context)?;
                 }
+                V::Shape(ref name, _) => {
+                    let arg = arg_values.first().unwrap_or(&V::Null);
+                    value = apply_shape(&proc, arg, name, pos, context)?;
+                }
                 _ => {
                     context.trace(pos.clone());
                     return Err(E::ExpectedType(Type::Union(vec![
-                        Type::Proc, Type::ForeignProc, Type::Type, Type::Rule("<ANY>".into())
+                        Type::Proc, Type::ForeignProc, Type::Type, Type::Rule("<ANY>".into()), Type::Shape("<ANY>".into())
                     ]), proc.typ()))
                 }
             }
@@ -839,6 +1604,10 @@ pub fn interpret(input_node: &Node, context: &mut Context) -> Result<(V, R), E>
         Node(N::Continue, _) => {
             Ok((V::Null, R::Continue))
         }
+        Node(N::Del(node), pos) => {
+            delete(node, pos, context)?;
+            Ok((V::Null, R::None))
+        }
         Node(N::Body(nodes), _) => {
             context.push();
             for n in nodes.iter() {
@@ -855,7 +1624,9 @@ pub fn interpret(input_node: &Node, context: &mut Context) -> Result<(V, R), E>
             let (value, _) = interpret(expr, context)?;
             match id_node.as_ref() {
                 Node(N::ID(id), id_pos) => {
-                    if context.get(id).is_some() {
+                    // rules of the prelude can be replaced by the script
+                    let from_prelude = matches!(context.get(id), Some(V::Rule(_, _, _, _, path)) if path == "<PRELUDE>");
+                    if context.get(id).is_some() && !from_prelude {
                         context.trace(id_pos.clone());
                         return Err(E::AlreadyDefined(id.clone()))
                     }
@@ -874,171 +1645,35 @@ pub fn interpret(input_node: &Node, context: &mut Context) -> Result<(V, R), E>
         }
         Node(N::OpAssign { op, id: id_node, expr }, pos) => {
             let (value, _) = interpret(expr, context)?;
-            let new_value = match id_node.as_ref() {
-                //                                this sucks, but it works
-                Node(N::ID(id), id_pos) => match (&mut context.clone()).get(id) {
-                    Some(old_value) => match op {
-                        T::Assign => Ok(value),
-                        T::AddAssign => binary(&T::Add, old_value, &value, pos, context),
-                        T::SubAssign => binary(&T::Sub, old_value, &value, pos, context),
-                        T::MulAssign => binary(&T::Mul, old_value, &value, pos, context),
-                        T::DivAssign => binary(&T::Div, old_value, &value, pos, context),
-                        T::ModAssign => binary(&T::Mod, old_value, &value, pos, context),
-                        _ => Ok(old_value.clone())
-                    }
-                    None => {
-                        context.trace(id_pos.clone());
-                        Err(E::NotDefined(id.clone()))
-                    }
-                }
-                Node(N::Field { head: head_node, field: field_node }, field_pos) =>
-                    match head_node.as_ref() {
-                        Node(N::ID(id), id_pos) => {
-                            let (head, _) = interpret(head_node, context)?;
-                            match get_field(&head, head_node, field_node, field_pos, context) {
-                                Ok(old_value) => match op {
-                                    T::Assign => Ok(value),
-                                    T::AddAssign => binary(&T::Add, &old_value, &value, pos, context),
-                                    T::SubAssign => binary(&T::Sub, &old_value, &value, pos, context),
-                                    T::MulAssign => binary(&T::Mul, &old_value, &value, pos, context),
-                                    T::DivAssign => binary(&T::Div, &old_value, &value, pos, context),
-                                    T::ModAssign => binary(&T::Mod, &old_value, &value, pos, context),
-                                    _ => Ok(old_value.clone())
-                                }
-                                Err(e) => {
-                                    context.trace(id_pos.clone());
-                                    Err(e)
-                                }
-                            }
-                        }
-                        _ => {
-                            context.trace(head_node.1.clone());
-                            Err(E::CannotAssign(head_node.0.clone()))
-                        }
-                    }
-                Node(N::FieldExpr { head: head_node, expr }, field_pos) => {
-                    let (field_value, _) = interpret(expr, context)?;
-                    match head_node.as_ref() {
-                        Node(N::ID(id), id_pos) => {
-                            let (head, _) = interpret(head_node, context)?;
-                            match get_field_value(&head, head_node, field_value, field_pos, field_pos, context) {
-                                Ok(old_value) => match op {
-                                    T::Assign => Ok(value),
-                                    T::AddAssign => binary(&T::Add, &old_value, &value, pos, context),
-                                    T::SubAssign => binary(&T::Sub, &old_value, &value, pos, context),
-                                    T::MulAssign => binary(&T::Mul, &old_value, &value, pos, context),
-                                    T::DivAssign => binary(&T::Div, &old_value, &value, pos, context),
-                                    T::ModAssign => binary(&T::Mod, &old_value, &value, pos, context),
-                                    _ => Ok(old_value.clone())
-                                }
-                                Err(e) => {
-                                    context.trace(id_pos.clone());
-                                    Err(e)
-                                }
-                            }
-                        }
-                        _ => {
-                            context.trace(head_node.1.clone());
-                            Err(E::CannotAssign(head_node.0.clone()))
-                        }
-                    }
-                }
-                _ => {
-                    context.trace(pos.clone());
-                    Err(E::CannotAssign(id_node.0.clone()))
-                }
-            }?;
-            assign_new_value(new_value, id_node, pos, context)?;
+            op_assign(op, id_node, value, pos, context)?;
             Ok((V::Null, R::None))
         }
         Node(N::Inc(id_node), pos) => {
-            let new_value = match id_node.as_ref() {
-                //                                this sucks, but it works
-                Node(N::ID(id), id_pos) => match (&mut context.clone()).get(id) {
-                    Some(old_value) => binary(&T::Add, old_value, &V::Int(1), pos, context),
-                    None => {
-                        context.trace(id_pos.clone());
-                        Err(E::NotDefined(id.clone()))
-                    }
-                }
-                Node(N::Field { head: head_node, field: field_node }, field_pos) =>
-                    match head_node.as_ref() {
-                        Node(N::ID(id), id_pos) => {
-                            let (head, _) = interpret(head_node, context)?;
-                            match get_field(&head, head_node, field_node, field_pos, context) {
-                                Ok(old_value) => binary(&T::Add, &old_value, &V::Int(1), pos, context),
-                                Err(e) => {
-                                    context.trace(id_pos.clone());
-                                    Err(e)
-                                }
-                            }
-                        }
-                        _ => {
-                            context.trace(head_node.1.clone());
-                            Err(E::CannotAssign(head_node.0.clone()))
-                        }
-                    }
-                _ => {
-                    context.trace(pos.clone());
-                    Err(E::CannotAssign(id_node.0.clone()))
-                }
-            }?;
-            assign_new_value(new_value, id_node, pos, context)?;
+            op_assign(&T::AddAssign, id_node, V::Int(1), pos, context)?;
             Ok((V::Null, R::None))
         }
         Node(N::Dec(id_node), pos) => {
-            let new_value = match id_node.as_ref() {
-                //                                this sucks, but it works
-                Node(N::ID(id), id_pos) => match (&mut context.clone()).get(id) {
-                    Some(old_value) => binary(&T::Sub, old_value, &V::Int(1), pos, context),
-                    None => {
-                        context.trace(id_pos.clone());
-                        Err(E::NotDefined(id.clone()))
-                    }
-                }
-                Node(N::Field { head: head_node, field: field_node }, field_pos) =>
-                    match head_node.as_ref() {
-                        Node(N::ID(id), id_pos) => {
-                            let (head, _) = interpret(head_node, context)?;
-                            match get_field(&head, head_node, field_node, field_pos, context) {
-                                Ok(old_value) => binary(&T::Sub, &old_value, &V::Int(1), pos, context),
-                                Err(e) => {
-                                    context.trace(id_pos.clone());
-                                    Err(e)
-                                }
-                            }
-                        }
-                        _ => {
-                            context.trace(head_node.1.clone());
-                            Err(E::CannotAssign(head_node.0.clone()))
-                        }
-                    }
-                _ => {
-                    context.trace(pos.clone());
-                    Err(E::CannotAssign(id_node.0.clone()))
-                }
-            }?;
-            assign_new_value(new_value, id_node, pos, context)?;
+            op_assign(&T::SubAssign, id_node, V::Int(1), pos, context)?;
             Ok((V::Null, R::None))
         }
         Node(N::Proc { name: name_node, params: param_nodes, body: body_node }, pos) => {
             if let Node(N::ID(id), name_pos) = name_node.as_ref() {
-                let mut params: Vec<(String, Option<Node>, bool)> = vec![];
+                let mut params: ProcValueParams = vec![];
                 for (param_node, type_node_, apply) in param_nodes {
                     if let Node(N::ID(id), param_pos) = param_node {
                         let mut typ: Option<Node> = None;
                         if let Some(type_node) = type_node_ {
-                            let (type_value, _) = interpret(type_node, context)?;
+                            let type_value = interpret(type_node, context)?.0.into_type();
                             if let V::Type(_) = type_value {
                                 typ = Some(type_node.clone());
-                            } else if let V::Rule(_, _, _) = type_value {
+                            } else if type_value.is_constraint() {
                                 typ = Some(type_node.clone());
                             } else {
                                 context.trace(type_node.1.clone());
                                 return Err(E::ExpectedType(Type::Type, type_value.typ()))
                             }
                         }
-                        params.push((id.clone(), typ, *apply))
+                        params.push((id.clone(), typ, *apply, false))
                     } else {
                         context.trace(param_node.1.clone());
                         return Err(E::ExpectedNode(N::ID("_".into()), param_node.0.clone()))
@@ -1051,15 +1686,53 @@ pub fn interpret(input_node: &Node, context: &mut Context) -> Result<(V, R), E>
                 Err(E::ExpectedNode(N::ID("_".into()), name_node.0.clone()))
             }
         }
-        Node(N::Rule { name: name_node, id: id_node, rules }, _) => {
+        Node(N::Rule { name: name_node, params: param_nodes, rules }, pos) => {
             if let Node(N::ID(name), name_pos) = name_node.as_ref() {
-                if let Node(N::ID(id), id_pos) = id_node.as_ref() {
-                    context.def(name, &V::Rule(name.clone(), id.clone(), rules.clone()));
-                    Ok((V::Null, R::None))
-                } else {
-                    context.trace(id_node.1.clone());
-                    Err(E::ExpectedNode(N::ID("_".into()), id_node.0.clone()))
+                let mut params: Vec<String> = vec![];
+                for param_node in param_nodes.iter() {
+                    if let Node(N::ID(param), param_pos) = param_node {
+                        params.push(param.clone());
+                    } else {
+                        context.trace(param_node.1.clone());
+                        return Err(E::ExpectedNode(N::ID("_".into()), param_node.0.clone()))
+                    }
+                }
+                if params.is_empty() {
+                    context.trace(pos.clone());
+                    return Err(E::ExpectedNode(N::ID("_".into()), N::Null))
+                }
+                context.def(name, &V::Rule(name.clone(), params, rules.clone(), vec![], context.path.clone()));
+                Ok((V::Null, R::None))
+            } else {
+                context.trace(name_node.1.clone());
+                Err(E::ExpectedNode(N::ID("_".into()), name_node.0.clone()))
+            }
+        }
+        Node(N::Shape { name: name_node, fields: field_nodes }, _) => {
+            if let Node(N::ID(name), name_pos) = name_node.as_ref() {
+                let mut fields: ShapeFields = vec![];
+                for (key_node, constraint_node) in field_nodes.iter() {
+                    if let Node(N::ID(key), key_pos) = key_node {
+                        let (constraint_node, optional) = match constraint_node {
+                            Node(N::Unary { op: T::Nullable, node }, _) => (node.as_ref(), true),
+                            _ => (constraint_node, false)
+                        };
+                        let constraint = interpret(constraint_node, context)?.0.into_type();
+                        if constraint.is_constraint() {
+                            fields.push((key.clone(), constraint, optional));
+                        } else {
+                            context.trace(constraint_node.1.clone());
+                            return Err(E::ExpectedType(Type::Union(vec![
+                                Type::Type, Type::Rule("<ANY>".into()), Type::Shape("<ANY>".into())
+                            ]), constraint.typ()))
+                        }
+                    } else {
+                        context.trace(key_node.1.clone());
+                        return Err(E::ExpectedNode(N::ID("_".into()), key_node.0.clone()))
+                    }
                 }
+                context.def(name, &V::Shape(name.clone(), fields));
+                Ok((V::Null, R::None))
             } else {
                 context.trace(name_node.1.clone());
                 Err(E::ExpectedNode(N::ID("_".into()), name_node.0.clone()))
@@ -1077,33 +1750,21 @@ pub fn interpret(input_node: &Node, context: &mut Context) -> Result<(V, R), E>
             }
         }
         Node(N::Call { id: id_node, args }, pos) => {
-            let (proc, _) = interpret(id_node, context)?;
+            let (proc, self_value) = get_callee(id_node, context)?;
             let mut arg_values: Vec<V> = vec![];
             let mut arg_poses: Vec<&Position> = vec![];
+            let self_pos;
+            if let Some((value, pos)) = self_value {
+                self_pos = pos;
+                arg_values.push(value);
+                arg_poses.push(&self_pos);
+            }
             for arg in args.iter() {
                 arg_poses.push(&arg.1);
                 let (value, _) = interpret(arg, context)?;
                 arg_values.push(value);
             }
-            let old_context = context.clone();
-            *context = Context::proc(context);
-            match proc {
-                V::Proc(ref params, ref body) => {
-                    assign_params(params, arg_values, arg_poses, context)?;
-                    context.trace(pos.clone());
-                    interpret(body, context)?;
-                }
-                V::ForeignProc(ref params, ref func) => {
-                    assign_params(params, arg_values, arg_poses, context)?;
-                    context.trace(pos.clone());
-                    func(context, pos)?;
-                }
-                _ => {
-                    context.trace(pos.clone());
-                    return Err(E::ExpectedType(Type::Union(vec![Type::Proc, Type::ForeignProc]), proc.typ()))
-                }
-            }
-            *context = old_context;
+            call_proc(&proc, arg_values, arg_poses, pos, context)?;
             Ok((V::Null, R::None))
         }
         Node(N::If { cond: cond_node, body, else_body }, _) => {
@@ -1128,10 +1789,11 @@ pub fn interpret(input_node: &Node, context: &mut Context) -> Result<(V, R), E>
         Node(N::For { param: param_node, iter: iter_node, body }, pos) => {
             if let Node(N::ID(param), param_pos) = param_node.as_ref() {
                 let (iter, _) = interpret(iter_node, context)?;
-                match iter {
-                    V::Vector(values, _) => {
-                        for v in values.iter() {
-                            context.set(param, v);
+                let typ = iter.typ();
+                match iter_values(iter) {
+                    Some(values) => {
+                        for v in values {
+                            context.set(param, &v);
                             let (value, ret) = interpret(body, context)?;
                             if ret == R::Return { return Ok((value, ret)) }
                             if ret == R::Break { break }
@@ -1139,39 +1801,9 @@ pub fn interpret(input_node: &Node, context: &mut Context) -> Result<(V, R), E>
                         context.del(param);
                         Ok((V::Null, R::None))
                     }
-                    V::String(string) => {
-                        for v in string.chars() {
-                            context.set(param, &V::String(v.to_string()));
-                            let (value, ret) = interpret(body, context)?;
-                            if ret == R::Return { return Ok((value, ret)) }
-                            if ret == R::Break { break }
-                        }
-                        context.del(param);
-                        Ok((V::Null, R::None))
-                    }
-                    V::Tuple(values) => {
-                        for v in values.iter() {
-                            context.set(param, v);
-                            let (value, ret) = interpret(body, context)?;
-                            if ret == R::Return { return Ok((value, ret)) }
-                            if ret == R::Break { break }
-                        }
-                        context.del(param);
-                        Ok((V::Null, R::None))
-                    }
-                    V::Object(values) => {
-                        for (k, v) in values.iter() {
-                            context.set(param, &V::Tuple(vec![V::String(k.clone()), v.clone()]));
-                            let (value, ret) = interpret(body, context)?;
-                            if ret == R::Return { return Ok((value, ret)) }
-                            if ret == R::Break { break }
-                        }
-                        context.del(param);
-                        Ok((V::Null, R::None))
-                    }
-                    _ => {
+                    None => {
                         context.trace(iter_node.1.clone());
-                        Err(E::InvalidIterator(iter.typ()))
+                        Err(E::InvalidIterator(typ))
                     }
                 }
             } else {
@@ -1194,14 +1826,17 @@ pub fn interpret(input_node: &Node, context: &mut Context) -> Result<(V, R), E>
                                 context.trace(step_node.1.clone());
                                 return Err(E::ExpectedType(Type::Int, step_value.typ()))
                             }
+                            if step == 0 {
+                                context.trace(step_node.1.clone());
+                                return Err(E::RangeStep)
+                            }
                         }
-                        let mut i: i64 = start;
-                        while i < end {
+                        // a negative step counts down while `i > end`
+                        for i in range_values(start, end, step) {
                             context.set(param, &V::Int(i));
                             let (value, ret) = interpret(body, context)?;
                             if ret == R::Return { return Ok((value, ret)) }
                             if ret == R::Break { break }
-                            i += step;
                         }
                         context.del(param);
                         Ok((V::Null, R::None))
@@ -0,0 +1,281 @@
+use crate::*;
+
+pub struct JsonParser {
+    chars: Vec<char>,
+    idx: usize,
+    ln: usize,
+    col: usize,
+}
+impl JsonParser {
+    pub fn new(text: &str) -> Self {
+        Self { chars: text.chars().collect(), idx: 0, ln: 0, col: 0 }
+    }
+    pub fn char(&self) -> Option<char> {
+        self.chars.get(self.idx).copied()
+    }
+    pub fn advance(&mut self) {
+        if self.char() == Some('\n') {
+            self.ln += 1;
+            self.col = 0;
+        } else {
+            self.col += 1;
+        }
+        self.idx += 1;
+    }
+    pub fn error(&self, msg: &str) -> E {
+        E::JsonParse(msg.to_string(), self.ln, self.col)
+    }
+    pub fn skip_ws(&mut self) {
+        while let Some(' ' | '\t' | '\n' | '\r') = self.char() { self.advance() }
+    }
+    pub fn expect(&mut self, c: char) -> Result<(), E> {
+        if self.char() != Some(c) {
+            return Err(match self.char() {
+                Some(got) => self.error(format!("expected {c:?}, got {got:?}").as_str()),
+                None => self.error(format!("expected {c:?}, got end of input").as_str()),
+            })
+        }
+        self.advance();
+        Ok(())
+    }
+    pub fn keyword(&mut self, word: &str, value: V) -> Result<V, E> {
+        for c in word.chars() {
+            if self.char() != Some(c) { return Err(self.error(format!("invalid literal, expected {word:?}").as_str())) }
+            self.advance();
+        }
+        Ok(value)
+    }
+    pub fn parse(&mut self) -> Result<V, E> {
+        self.skip_ws();
+        let value = self.value()?;
+        self.skip_ws();
+        if let Some(c) = self.char() {
+            return Err(self.error(format!("unexpected {c:?} after value").as_str()))
+        }
+        Ok(value)
+    }
+    pub fn value(&mut self) -> Result<V, E> {
+        match self.char() {
+            Some('n') => self.keyword("null", V::Null),
+            Some('t') => self.keyword("true", V::Bool(true)),
+            Some('f') => self.keyword("false", V::Bool(false)),
+            Some('"') => Ok(V::String(self.string()?)),
+            Some('[') => {
+                self.advance();
+                self.skip_ws();
+                let mut values: Vec<V> = vec![];
+                if self.char() == Some(']') {
+                    self.advance();
+                    return Ok(V::Vector(values, vec![]))
+                }
+                loop {
+                    self.skip_ws();
+                    values.push(self.value()?);
+                    self.skip_ws();
+                    match self.char() {
+                        Some(',') => self.advance(),
+                        Some(']') => { self.advance(); break }
+                        _ => return Err(self.error("expected ',' or ']' in array"))
+                    }
+                }
+                let types = V::create_union(values.clone());
+                Ok(V::Vector(values, types))
+            }
+            Some('{') => {
+                self.advance();
+                self.skip_ws();
+                let mut fields: OrderedMap<String, V> = OrderedMap::new();
+                if self.char() == Some('}') {
+                    self.advance();
+                    return Ok(V::Object(fields))
+                }
+                loop {
+                    self.skip_ws();
+                    if self.char() != Some('"') { return Err(self.error("expected string key in object")) }
+                    let key = self.string()?;
+                    self.skip_ws();
+                    self.expect(':')?;
+                    self.skip_ws();
+                    let value = self.value()?;
+                    fields.insert(key, value);
+                    self.skip_ws();
+                    match self.char() {
+                        Some(',') => self.advance(),
+                        Some('}') => { self.advance(); break }
+                        _ => return Err(self.error("expected ',' or '}' in object"))
+                    }
+                }
+                Ok(V::Object(fields))
+            }
+            Some('-' | '0'..='9') => self.number(),
+            Some(c) => Err(self.error(format!("unexpected {c:?}").as_str())),
+            None => Err(self.error("unexpected end of input")),
+        }
+    }
+    pub fn number(&mut self) -> Result<V, E> {
+        let mut number = String::new();
+        let mut float = false;
+        if self.char() == Some('-') { number.push('-'); self.advance(); }
+        while let Some(c) = self.char() {
+            match c {
+                '0'..='9' => number.push(c),
+                '.' | 'e' | 'E' | '+' | '-' => { float = true; number.push(c) }
+                _ => break
+            }
+            self.advance();
+        }
+        if float {
+            match number.parse::<f64>() {
+                Ok(v) => Ok(V::Float(v)),
+                Err(_) => Err(self.error(format!("invalid number {number:?}").as_str()))
+            }
+        } else {
+            match number.parse::<i64>() {
+                Ok(v) => Ok(V::Int(v)),
+                Err(_) => match number.parse::<f64>() {
+                    Ok(v) => Ok(V::Float(v)),
+                    Err(_) => Err(self.error(format!("invalid number {number:?}").as_str()))
+                }
+            }
+        }
+    }
+    pub fn hex(&mut self) -> Result<u32, E> {
+        let mut code: u32 = 0;
+        for _ in 0..4 {
+            match self.char().and_then(|c| c.to_digit(16)) {
+                Some(d) => code = code * 16 + d,
+                None => return Err(self.error("invalid unicode escape"))
+            }
+            self.advance();
+        }
+        Ok(code)
+    }
+    pub fn string(&mut self) -> Result<String, E> {
+        self.expect('"')?;
+        let mut s = String::new();
+        loop {
+            match self.char() {
+                Some('"') => { self.advance(); break }
+                Some('\\') => {
+                    self.advance();
+                    match self.char() {
+                        Some('"') => s.push('"'),
+                        Some('\\') => s.push('\\'),
+                        Some('/') => s.push('/'),
+                        Some('b') => s.push('\u{8}'),
+                        Some('f') => s.push('\u{c}'),
+                        Some('n') => s.push('\n'),
+                        Some('r') => s.push('\r'),
+                        Some('t') => s.push('\t'),
+                        Some('u') => {
+                            self.advance();
+                            let mut code = self.hex()?;
+                            if (0xD800..0xDC00).contains(&code) {
+                                self.expect('\\')?;
+                                self.expect('u')?;
+                                let low = self.hex()?;
+                                if !(0xDC00..0xE000).contains(&low) { return Err(self.error("invalid surrogate pair")) }
+                                code = 0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00);
+                            }
+                            match char::from_u32(code) {
+                                Some(c) => s.push(c),
+                                None => return Err(self.error("invalid unicode escape"))
+                            }
+                            continue
+                        }
+                        _ => return Err(self.error("invalid escape sequence"))
+                    }
+                    self.advance();
+                }
+                Some(c) => { s.push(c); self.advance(); }
+                None => return Err(self.error("unterminated string"))
+            }
+        }
+        Ok(s)
+    }
+}
+
+pub fn parse_json(text: &str) -> Result<V, E> {
+    JsonParser::new(text).parse()
+}
+
+pub fn json_string(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(format!("\\u{:04x}", c as u32).as_str()),
+            c => out.push(c)
+        }
+    }
+    out.push('"');
+    out
+}
+pub fn stringify_json(value: &V, pretty: bool, indent: usize) -> Result<String, E> {
+    let (s, s_inner, nl, sep) = if pretty {
+        ("    ".repeat(indent), "    ".repeat(indent + 1), "\n", ": ")
+    } else {
+        (String::new(), String::new(), "", ":")
+    };
+    let items = |values: &Vec<V>| -> Result<String, E> {
+        if values.is_empty() { return Ok("[]".into()) }
+        let mut strings: Vec<String> = vec![];
+        for v in values.iter() {
+            strings.push(format!("{s_inner}{}", stringify_json(v, pretty, indent + 1)?));
+        }
+        Ok(format!("[{nl}{}{nl}{s}]", strings.join(format!(",{nl}").as_str())))
+    };
+    match value {
+        V::Null => Ok("null".into()),
+        V::Bool(v) => Ok(v.to_string()),
+        V::Int(v) => Ok(v.to_string()),
+        V::BigInt(v) => Ok(v.to_string()),
+        V::Float(v) => if v.is_finite() {
+            Ok(format!("{v:?}"))
+        } else {
+            Err(E::Unserializable(value.clone()))
+        }
+        V::String(v) => Ok(json_string(v)),
+        V::Vector(values, _) => items(values),
+        V::Tuple(values) => items(values),
+        V::Object(fields) => {
+            if fields.is_empty() { return Ok("{}".into()) }
+            let mut strings: Vec<String> = vec![];
+            for (k, v) in fields.iter() {
+                strings.push(format!("{s_inner}{}{sep}{}", json_string(k), stringify_json(v, pretty, indent + 1)?));
+            }
+            Ok(format!("{{{nl}{}{nl}{s}}}", strings.join(format!(",{nl}").as_str())))
+        }
+        _ => Err(E::Unserializable(value.clone()))
+    }
+}
+
+pub fn json_parse(context: &mut Context, pos: &Position) -> Result<V, E> {
+    let s = context.get(&String::from("s")).unwrap();
+    if let V::String(s) = s {
+        match parse_json(s) {
+            Ok(v) => Ok(v),
+            Err(e) => {
+                context.trace(pos.clone());
+                Err(e)
+            }
+        }
+    } else {
+        Err(E::ExpectedType(Type::String, s.typ()))
+    }
+}
+pub fn json_stringify(context: &mut Context, pos: &Position) -> Result<V, E> {
+    let v = context.get(&String::from("v")).unwrap();
+    let pretty = context.get(&String::from("pretty")).unwrap() == &V::Bool(true);
+    match stringify_json(v, pretty, 0) {
+        Ok(s) => Ok(V::String(s)),
+        Err(e) => {
+            context.trace(pos.clone());
+            Err(e)
+        }
+    }
+}
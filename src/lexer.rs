@@ -1,25 +1,20 @@
 use std::cmp::min;
 use crate::*;
 
-static DIGITS: [&str; 10] = ["0", "1", "2", "3", "4", "5", "6", "7", "8", "9"];
-static LETTERS: [&str; 53] = [
-    "a", "b", "c", "d", "e", "f", "g", "h", "i", "j", "k", "l", "m", "n", "o", "p", "q", "r",
-    "s", "t", "u", "v", "w", "x", "y", "z", "A", "B", "C", "D", "E", "F", "G", "H", "I", "J",
-    "K", "L", "M", "N", "O", "P", "Q", "R", "S", "T", "U", "V", "W", "X", "Y", "Z", "_"
-];
-
 #[derive(Debug, Clone, PartialEq)]
 pub enum T {
     EOF, EOL, Indent(u16),
-    Rule, Container, Proc, If, Else, While, For,
+    Rule, Shape, Container, Proc, If, Else, While, For,
     Var, Global,
-    Return, Break, Continue,
-//  !     =       :    <-  ->   #    ?         |       .      ..     ,
-    Call, Assign, Rep, In, Out, Len, Nullable, Option, Field, Range, Sep,
+    Return, Break, Continue, Del,
+//  !     =       :    <-  ->   #    ?         |       .      ..     ..=             ,
+    Call, Assign, Rep, In, Out, Len, Nullable, Option, Field, Range, RangeInclusive, Sep,
 //  (       )        [         ]          {         }
     EvalIn, EvalOut, VectorIn, VectorOut, ObjectIn, ObjectOut,
-//  +    -    *    /    %    ==  !=  <   >   <=  >=
-    Add, Sub, Mul, Div, Mod, EQ, NE, LT, GT, LE, GE,
+//  +    -    *    /    %    ^    ==  !=  <   >   <=  >=
+    Add, Sub, Mul, Div, Mod, Pow, EQ, NE, LT, GT, LE, GE,
+//  &       ~       <<   >>
+    BitAnd, BitNot, Shl, Shr,
 //  +=         -=         *=          /=         %=
     AddAssign, SubAssign, MulAssign, DivAssign, ModAssign,
 //  ++   --
@@ -28,7 +23,7 @@ pub enum T {
     Is, Contains, And, Or, Xor, Not,
 //  _
     Wildcard, Null,
-    Int(i64), Float(f64), Bool(bool), String(String),
+    Int(i64), Float(f64), Bool(bool), String(String), Template(Vec<TemplatePart>), Bytes(Vec<u8>),
     ID(String), Type(Type)
 }
 impl std::fmt::Display for T {
@@ -43,6 +38,7 @@ impl T {
             Self::EOL => "end of line",
             Self::Indent(_) => "indention",
             Self::Rule => "'rule'",
+            Self::Shape => "'shape'",
             Self::Container => "'container'",
             Self::Proc => "'proc'",
             Self::If => "'if'",
@@ -54,6 +50,7 @@ impl T {
             Self::Return => "'return'",
             Self::Break => "'break'",
             Self::Continue => "'continue'",
+            Self::Del => "'del'",
             Self::Call => "'!'",
             Self::Assign => "'='",
             Self::Rep => "':'",
@@ -64,6 +61,7 @@ impl T {
             Self::Option => "'|'",
             Self::Field => "'.'",
             Self::Range => "'..'",
+            Self::RangeInclusive => "'..='",
             Self::Sep => "','",
             Self::EvalIn => "'('",
             Self::EvalOut => "')'",
@@ -76,6 +74,11 @@ impl T {
             Self::Mul => "'*'",
             Self::Div => "'/'",
             Self::Mod => "'%'",
+            Self::Pow => "'^'",
+            Self::BitAnd => "'&'",
+            Self::BitNot => "'~'",
+            Self::Shl => "'<<'",
+            Self::Shr => "'>>'",
             Self::EQ => "'=='",
             Self::NE => "'!='",
             Self::LT => "'<'",
@@ -101,12 +104,14 @@ impl T {
             Self::Float(_) => "float",
             Self::Bool(_) => "bool",
             Self::String(_) => "str",
+            Self::Template(_) => "template string",
+            Self::Bytes(_) => "bytes",
             Self::ID(_) => "id",
             Self::Type(_) => "type",
         }
     }
 }
-#[derive(Clone)]
+#[derive(Clone, PartialEq)]
 pub struct Token(pub T, pub Position);
 impl std::fmt::Debug for Token {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -114,195 +119,442 @@ impl std::fmt::Debug for Token {
     }
 }
 
+/// a piece of a string literal, either text or the tokens of an embedded `{expr}`
+#[derive(Debug, Clone, PartialEq)]
+pub enum TemplatePart {
+    Str(String), Expr(Vec<Token>)
+}
+fn push_char(parts: &mut Vec<TemplatePart>, c: char) {
+    match parts.last_mut() {
+        Some(TemplatePart::Str(s)) => s.push(c),
+        _ => parts.push(TemplatePart::Str(c.to_string()))
+    }
+}
+/// a plain string token, or a template token if the string has embedded expressions
+fn string_token(parts: Vec<TemplatePart>) -> T {
+    if parts.iter().any(|part| matches!(part, TemplatePart::Expr(_))) {
+        return T::Template(parts)
+    }
+    match parts.into_iter().next() {
+        Some(TemplatePart::Str(s)) => T::String(s),
+        _ => T::String(String::new())
+    }
+}
+/// moves a token lexed on its own to where it is in the line
+fn shift_token(Token(t, pos): Token, ln: usize, col: usize) -> Token {
+    let t = match t {
+        T::Template(parts) => T::Template(parts.into_iter().map(|part| match part {
+            TemplatePart::Expr(tokens) => TemplatePart::Expr(tokens.into_iter().map(|token| shift_token(token, ln, col)).collect()),
+            part => part
+        }).collect()),
+        t => t
+    };
+    Token(t, Position::new(ln..ln+1, pos.1.start+col..pos.1.end+col))
+}
+/// lexes the expression of a `{expr}` in a string, starting after the `{`, returns its tokens and the column after the `}`,
+/// `None` if there is no `}` on the line or nothing between the braces, then the `{` is part of the text
+fn lex_embedded(path: &String, line: &str, col: usize, ln: usize, context: &mut Context) -> Result<Option<(Vec<Token>, usize)>, E> {
+    let mut depth: usize = 1;
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+    let mut end = None;
+    for (i, c) in line[col..].char_indices() {
+        match quote {
+            Some(q) => if escaped {
+                escaped = false
+            } else if c == '\\' {
+                escaped = true
+            } else if c == q {
+                quote = None
+            }
+            None => match c {
+                '"' | '\'' => quote = Some(c),
+                '{' => depth += 1,
+                '}' => {
+                    depth -= 1;
+                    if depth == 0 { end = Some(col + i); break }
+                }
+                _ => {}
+            }
+        }
+    }
+    let end = match end {
+        Some(end) if !line[col..end].trim().is_empty() => end,
+        _ => return Ok(None)
+    };
+    let trace_len = context.trace.len();
+    match lex(path, &line[col..end].to_string(), context) {
+        Ok(lines) => {
+            let tokens = lines.into_iter().next().unwrap_or_default().into_iter()
+                .filter(|token| !matches!(token.0, T::Indent(_) | T::EOL))
+                .map(|token| shift_token(token, ln, col)).collect();
+            Ok(Some((tokens, end + 1)))
+        }
+        Err(e) => {
+            for (pos, _) in context.trace[trace_len..].iter_mut() {
+                *pos = Position::new(ln..ln+1, pos.1.start+col..pos.1.end+col);
+            }
+            Err(e)
+        }
+    }
+}
+/// how a string literal ends and how its contents are read
+struct Quote {
+    end: &'static str,
+    raw: bool,
+    interpolate: bool
+}
+const TRIPLE_QUOTE: Quote = Quote { end: "\"\"\"", raw: false, interpolate: true };
+const DOUBLE_QUOTE: Quote = Quote { end: "\"", raw: false, interpolate: true };
+const SINGLE_QUOTE: Quote = Quote { end: "'", raw: false, interpolate: false };
+const RAW_QUOTE: Quote = Quote { end: "\"", raw: true, interpolate: false };
+
+/// reads the contents of a string literal from `col` up to the end of the `quote` into `parts`, resolving escape sequences
+/// unless the string is raw and lexing `{expr}` if it interpolates. returns the column after the closing quote and whether the quote was found
+fn lex_string(path: &String, line: &str, mut col: usize, ln: usize, quote: &Quote, parts: &mut Vec<TemplatePart>, context: &mut Context) -> Result<(usize, bool), E> {
+    let Quote { end: quote, raw, interpolate } = *quote;
+    while col < line.len() {
+        if line[col..].starts_with(quote) { return Ok((col + quote.len(), true)) }
+        let c = line[col..].chars().next().unwrap();
+        col += c.len_utf8();
+        if c == '{' && interpolate {
+            if let Some((tokens, end)) = lex_embedded(path, line, col, ln, context)? {
+                col = end;
+                parts.push(TemplatePart::Expr(tokens));
+                continue
+            }
+        }
+        if c != '\\' || raw {
+            push_char(parts, c);
+            continue
+        }
+        let start = col - 1;
+        let c = match line[col..].chars().next() {
+            Some(c) => c,
+            None => {
+                context.trace(Position::new(ln..ln+1, start..col));
+                return Err(E::InvalidEscape("\\".into()))
+            }
+        };
+        col += c.len_utf8();
+        push_char(parts, match c {
+            'n' => '\n',
+            't' => '\t',
+            'r' => '\r',
+            '0' => '\0',
+            '\\' | '"' | '\'' | '{' | '}' => c,
+            'u' => {
+                let code = if line[col..].starts_with("{") {
+                    match line[col..].find("}") {
+                        Some(end) => {
+                            let code = u32::from_str_radix(&line[col+1..col+end], 16).ok().and_then(char::from_u32);
+                            col += end + 1;
+                            code
+                        }
+                        None => None
+                    }
+                } else {
+                    None
+                };
+                match code {
+                    Some(c) => c,
+                    None => {
+                        context.trace(Position::new(ln..ln+1, start..col));
+                        return Err(E::InvalidEscape(line[start..col].to_string()))
+                    }
+                }
+            }
+            _ => {
+                context.trace(Position::new(ln..ln+1, start..col));
+                return Err(E::InvalidEscape(line[start..col].to_string()))
+            }
+        });
+    }
+    Ok((col, false))
+}
+/// lexes the rest of a `b"..."` literal, characters are UTF-8 encoded and `\xNN` writes a single byte
+fn lex_bytes(line: &str, mut col: usize, ln: usize, context: &mut Context) -> Result<(Vec<u8>, usize), E> {
+    let start = col - 2;
+    let mut bytes: Vec<u8> = vec![];
+    while col < line.len() {
+        let c = line[col..].chars().next().unwrap();
+        col += c.len_utf8();
+        match c {
+            '"' => return Ok((bytes, col)),
+            '\\' => {
+                let escape_start = col - 1;
+                let c = line[col..].chars().next();
+                col += c.map(|c| c.len_utf8()).unwrap_or(0);
+                bytes.push(match c {
+                    Some('n') => b'\n',
+                    Some('t') => b'\t',
+                    Some('r') => b'\r',
+                    Some('0') => 0,
+                    Some(c) if matches!(c, '\\' | '"' | '\'') => c as u8,
+                    Some('x') => match line.get(col..col+2).and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+                        Some(b) => { col += 2; b }
+                        None => {
+                            context.trace(Position::new(ln..ln+1, escape_start..col));
+                            return Err(E::InvalidEscape(line[escape_start..col].to_string()))
+                        }
+                    }
+                    _ => {
+                        context.trace(Position::new(ln..ln+1, escape_start..col));
+                        return Err(E::InvalidEscape(line[escape_start..col].to_string()))
+                    }
+                });
+            }
+            c => bytes.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes())
+        }
+    }
+    context.trace(Position::new(ln..ln+1, start..col));
+    Err(E::UnterminatedString)
+}
+
 pub fn lex(path: &String, text: &String, context: &mut Context) -> Result<Vec<Vec<Token>>, E> {
     let mut tokens: Vec<Vec<Token>> = vec![];
+    // the index of the token line, lines inside a multi-line string continue the line the string started on
+    let mut tln: usize = 0;
+    // contents, line and column of a multi-line string which isn't closed yet
+    let mut open_string: Option<(Vec<TemplatePart>, usize, usize)> = None;
     for (ln, line) in text.split("\n").enumerate() {
-        tokens.push(vec![]);
+        // with CRLF line endings the `\r` isn't part of the line, nor of a multi-line string going over it
+        let line = line.strip_suffix('\r').unwrap_or(line);
         let mut col: usize = 0;
-        if col == line.len() { continue }
-        if [" ", "\t"].contains(&&line[col..col+1]) {
+        if let Some((mut parts, start_ln, start_col)) = open_string.take() {
+            let (end, closed) = lex_string(path, line, col, ln, &TRIPLE_QUOTE, &mut parts, context)?;
+            if !closed {
+                push_char(&mut parts, '\n');
+                open_string = Some((parts, start_ln, start_col));
+                continue
+            }
+            col = end;
+            tokens[tln].push(Token(string_token(parts), Position::new(start_ln..ln+1, start_col..col)));
+        } else {
+            tokens.push(vec![]);
+            tln = tokens.len() - 1;
+            if line.is_empty() { continue }
+        }
+        if col == 0 && line.starts_with([' ', '\t']) {
             let start = col;
             let mut indent: u16 = 0;
-            while col < line.len() && [" ", "\t"].contains(&&line[col..col+1]) {
-                indent += if &line[col..col+1] == "\t" { 4 } else { 1 };
+            while line[col..].starts_with([' ', '\t']) {
+                indent += if line[col..].starts_with('\t') { 4 } else { 1 };
                 col += 1;
             }
             if col < line.len() {
-                tokens[ln].push(Token(T::Indent(indent), Position::new(ln..ln+1, start..col+1)));
+                tokens[tln].push(Token(T::Indent(indent), Position::new(ln..ln+1, start..col+1)));
             }
         }
         while col < line.len() {
-            match &line[col..col+1] {
+            let c = line[col..].chars().next().unwrap();
+            match c {
                 // white space
-                " "|"\t"|"\r" => col += 1,
-                "\"" => {
+                ' ' | '\t' | '\r' => col += 1,
+                '"' | '\'' => {
                     let start = col;
-                    col += 1;
-                    let mut s = String::new();
-                    while col < line.len() {
-                        if &line[col..col+1] == "\"" { break }
-                        s.push_str(&line[col..col+1]);
-                        col += 1;
+                    let quote = if line[col..].starts_with("\"\"\"") { &TRIPLE_QUOTE } else if c == '"' { &DOUBLE_QUOTE } else { &SINGLE_QUOTE };
+                    col += quote.end.len();
+                    let mut parts: Vec<TemplatePart> = vec![];
+                    let (end, closed) = lex_string(path, line, col, ln, quote, &mut parts, context)?;
+                    col = end;
+                    if !closed {
+                        if quote.end == TRIPLE_QUOTE.end {
+                            push_char(&mut parts, '\n');
+                            open_string = Some((parts, ln, start));
+                            break
+                        }
+                        context.trace(Position::new(ln..ln+1, start..col));
+                        return Err(E::UnterminatedString)
                     }
-                    col += 1;
-                    tokens[ln].push(Token(T::String(s), Position::new(ln..ln+1, start..col+1)));
+                    tokens[tln].push(Token(string_token(parts), Position::new(ln..ln+1, start..col)));
                 }
-                "!" => {
+                '!' => {
                     let start = col;
                     col += 1;
                     if line.get(col..col+1) == Some("=") {
                         col += 1;
-                        tokens[ln].push(Token(T::NE, Position::new(ln..ln+1, start..col)));
+                        tokens[tln].push(Token(T::NE, Position::new(ln..ln+1, start..col)));
                         continue
                     }
-                    tokens[ln].push(Token(T::Call, Position::new(ln..ln+1, start..col)));
+                    tokens[tln].push(Token(T::Call, Position::new(ln..ln+1, start..col)));
                 }
-                "=" => {
+                '=' => {
                     let start = col;
                     col += 1;
                     if line.get(col..col+1) == Some("=") {
                         col += 1;
-                        tokens[ln].push(Token(T::EQ, Position::new(ln..ln+1, start..col)));
+                        tokens[tln].push(Token(T::EQ, Position::new(ln..ln+1, start..col)));
                         continue
                     }
-                    tokens[ln].push(Token(T::Assign, Position::new(ln..ln+1, start..col)));
+                    tokens[tln].push(Token(T::Assign, Position::new(ln..ln+1, start..col)));
                 }
-                ":" => {
+                ':' => {
                     let start = col;
                     col += 1;
-                    tokens[ln].push(Token(T::Rep, Position::new(ln..ln+1, start..col)));
+                    tokens[tln].push(Token(T::Rep, Position::new(ln..ln+1, start..col)));
                 }
-                "<" => {
+                '<' => {
                     let start = col;
                     col += 1;
                     if line.get(col..col+1) == Some("-") {
                         col += 1;
-                        tokens[ln].push(Token(T::In, Position::new(ln..ln+1, start..col)));
+                        tokens[tln].push(Token(T::In, Position::new(ln..ln+1, start..col)));
                         continue
                     }
                     if line.get(col..col+1) == Some("=") {
                         col += 1;
-                        tokens[ln].push(Token(T::LE, Position::new(ln..ln+1, start..col)));
+                        tokens[tln].push(Token(T::LE, Position::new(ln..ln+1, start..col)));
                         continue
                     }
-                    tokens[ln].push(Token(T::LT, Position::new(ln..ln+1, start..col)));
+                    if line.get(col..col+1) == Some("<") {
+                        col += 1;
+                        tokens[tln].push(Token(T::Shl, Position::new(ln..ln+1, start..col)));
+                        continue
+                    }
+                    tokens[tln].push(Token(T::LT, Position::new(ln..ln+1, start..col)));
                 }
-                ">" => {
+                '>' => {
                     let start = col;
                     col += 1;
                     if line.get(col..col+1) == Some("=") {
                         col += 1;
-                        tokens[ln].push(Token(T::GE, Position::new(ln..ln+1, start..col)));
+                        tokens[tln].push(Token(T::GE, Position::new(ln..ln+1, start..col)));
+                        continue
+                    }
+                    if line.get(col..col+1) == Some(">") {
+                        col += 1;
+                        tokens[tln].push(Token(T::Shr, Position::new(ln..ln+1, start..col)));
                         continue
                     }
-                    tokens[ln].push(Token(T::GT, Position::new(ln..ln+1, start..col)));
+                    tokens[tln].push(Token(T::GT, Position::new(ln..ln+1, start..col)));
+                }
+                '#' => {
+                    let start = col;
+                    col += 1;
+                    tokens[tln].push(Token(T::Len, Position::new(ln..ln+1, start..col)));
+                }
+                '?' => {
+                    let start = col;
+                    col += 1;
+                    tokens[tln].push(Token(T::Nullable, Position::new(ln..ln+1, start..col)));
                 }
-                "#" => {
+                '|' => {
                     let start = col;
                     col += 1;
-                    tokens[ln].push(Token(T::Len, Position::new(ln..ln+1, start..col)));
+                    tokens[tln].push(Token(T::Option, Position::new(ln..ln+1, start..col)));
                 }
-                "?" => {
+                '&' => {
                     let start = col;
                     col += 1;
-                    tokens[ln].push(Token(T::Nullable, Position::new(ln..ln+1, start..col)));
+                    tokens[tln].push(Token(T::BitAnd, Position::new(ln..ln+1, start..col)));
                 }
-                "|" => {
+                '~' => {
                     let start = col;
                     col += 1;
-                    tokens[ln].push(Token(T::Option, Position::new(ln..ln+1, start..col)));
+                    tokens[tln].push(Token(T::BitNot, Position::new(ln..ln+1, start..col)));
                 }
-                "." => {
+                '.' => {
                     let start = col;
                     col += 1;
                     if line.get(col..col+1) == Some(".") {
                         col += 1;
-                        tokens[ln].push(Token(T::Range, Position::new(ln..ln+1, start..col)));
+                        if line.get(col..col+1) == Some("=") {
+                            col += 1;
+                            tokens[tln].push(Token(T::RangeInclusive, Position::new(ln..ln+1, start..col)));
+                            continue
+                        }
+                        tokens[tln].push(Token(T::Range, Position::new(ln..ln+1, start..col)));
                         continue
                     }
-                    tokens[ln].push(Token(T::Field, Position::new(ln..ln+1, start..col)));
+                    tokens[tln].push(Token(T::Field, Position::new(ln..ln+1, start..col)));
                 }
-                "," => {
+                ',' => {
                     let start = col;
                     col += 1;
-                    tokens[ln].push(Token(T::Sep, Position::new(ln..ln+1, start..col)));
+                    tokens[tln].push(Token(T::Sep, Position::new(ln..ln+1, start..col)));
                 }
-                "(" => {
+                '(' => {
                     let start = col;
                     col += 1;
-                    tokens[ln].push(Token(T::EvalIn, Position::new(ln..ln+1, start..col)));
+                    tokens[tln].push(Token(T::EvalIn, Position::new(ln..ln+1, start..col)));
                 }
-                ")" => {
+                ')' => {
                     let start = col;
                     col += 1;
-                    tokens[ln].push(Token(T::EvalOut, Position::new(ln..ln+1, start..col)));
+                    tokens[tln].push(Token(T::EvalOut, Position::new(ln..ln+1, start..col)));
                 }
-                "[" => {
+                '[' => {
                     let start = col;
                     col += 1;
-                    tokens[ln].push(Token(T::VectorIn, Position::new(ln..ln+1, start..col)));
+                    tokens[tln].push(Token(T::VectorIn, Position::new(ln..ln+1, start..col)));
                 }
-                "]" => {
+                ']' => {
                     let start = col;
                     col += 1;
-                    tokens[ln].push(Token(T::VectorOut, Position::new(ln..ln+1, start..col)));
+                    tokens[tln].push(Token(T::VectorOut, Position::new(ln..ln+1, start..col)));
                 }
-                "{" => {
+                '{' => {
                     let start = col;
                     col += 1;
-                    tokens[ln].push(Token(T::ObjectIn, Position::new(ln..ln+1, start..col)));
+                    tokens[tln].push(Token(T::ObjectIn, Position::new(ln..ln+1, start..col)));
                 }
-                "}" => {
+                '}' => {
                     let start = col;
                     col += 1;
-                    tokens[ln].push(Token(T::ObjectOut, Position::new(ln..ln+1, start..col)));
+                    tokens[tln].push(Token(T::ObjectOut, Position::new(ln..ln+1, start..col)));
                 }
-                "+" => {
+                '+' => {
                     let start = col;
                     col += 1;
                     if line.get(col..col+1) == Some("+") {
                         col += 1;
-                        tokens[ln].push(Token(T::Inc, Position::new(ln..ln+1, start..col)));
+                        tokens[tln].push(Token(T::Inc, Position::new(ln..ln+1, start..col)));
                         continue
                     }
                     if line.get(col..col+1) == Some("=") {
                         col += 1;
-                        tokens[ln].push(Token(T::AddAssign, Position::new(ln..ln+1, start..col)));
+                        tokens[tln].push(Token(T::AddAssign, Position::new(ln..ln+1, start..col)));
                         continue
                     }
-                    tokens[ln].push(Token(T::Add, Position::new(ln..ln+1, start..col)));
+                    tokens[tln].push(Token(T::Add, Position::new(ln..ln+1, start..col)));
                 }
-                "-" => {
+                '-' => {
                     let start = col;
                     col += 1;
                     if line.get(col..col+1) == Some("-") {
                         col += 1;
-                        tokens[ln].push(Token(T::Dec, Position::new(ln..ln+1, start..col)));
+                        tokens[tln].push(Token(T::Dec, Position::new(ln..ln+1, start..col)));
                         continue
                     }
                     if line.get(col..col+1) == Some("=") {
                         col += 1;
-                        tokens[ln].push(Token(T::SubAssign, Position::new(ln..ln+1, start..col)));
+                        tokens[tln].push(Token(T::SubAssign, Position::new(ln..ln+1, start..col)));
                         continue
                     }
                     if line.get(col..col+1) == Some(">") {
                         col += 1;
-                        tokens[ln].push(Token(T::Out, Position::new(ln..ln+1, start..col)));
+                        tokens[tln].push(Token(T::Out, Position::new(ln..ln+1, start..col)));
                         continue
                     }
-                    tokens[ln].push(Token(T::Sub, Position::new(ln..ln+1, start..col)));
+                    tokens[tln].push(Token(T::Sub, Position::new(ln..ln+1, start..col)));
                 }
-                "*" => {
+                '*' => {
                     let start = col;
                     col += 1;
                     if line.get(col..col+1) == Some("=") {
                         col += 1;
-                        tokens[ln].push(Token(T::MulAssign, Position::new(ln..ln+1, start..col)));
+                        tokens[tln].push(Token(T::MulAssign, Position::new(ln..ln+1, start..col)));
+                        continue
+                    }
+                    if line.get(col..col+1) == Some("*") {
+                        col += 1;
+                        tokens[tln].push(Token(T::Pow, Position::new(ln..ln+1, start..col)));
                         continue
                     }
-                    tokens[ln].push(Token(T::Mul, Position::new(ln..ln+1, start..col)));
+                    tokens[tln].push(Token(T::Mul, Position::new(ln..ln+1, start..col)));
                 }
-                "/" => {
+                '/' => {
                     let start = col;
                     col += 1;
                     if line.get(col..col+1) == Some("/") {
@@ -312,36 +564,64 @@ pub fn lex(path: &String, text: &String, context: &mut Context) -> Result<Vec<Ve
                     }
                     if line.get(col..col+1) == Some("=") {
                         col += 1;
-                        tokens[ln].push(Token(T::DivAssign, Position::new(ln..ln+1, start..col)));
+                        tokens[tln].push(Token(T::DivAssign, Position::new(ln..ln+1, start..col)));
                         continue
                     }
-                    tokens[ln].push(Token(T::Div, Position::new(ln..ln+1, start..col)));
+                    tokens[tln].push(Token(T::Div, Position::new(ln..ln+1, start..col)));
                 }
-                "%" => {
+                '%' => {
                     let start = col;
                     col += 1;
                     if line.get(col..col+1) == Some("=") {
                         col += 1;
-                        tokens[ln].push(Token(T::ModAssign, Position::new(ln..ln+1, start..col)));
+                        tokens[tln].push(Token(T::ModAssign, Position::new(ln..ln+1, start..col)));
                         continue
                     }
-                    tokens[ln].push(Token(T::Mod, Position::new(ln..ln+1, start..col)));
+                    tokens[tln].push(Token(T::Mod, Position::new(ln..ln+1, start..col)));
+                }
+                '^' => {
+                    let start = col;
+                    col += 1;
+                    tokens[tln].push(Token(T::Pow, Position::new(ln..ln+1, start..col)));
                 }
                 _ => {
+                    // bytes
+                    if line[col..].starts_with("b\"") {
+                        let start = col;
+                        let (bytes, end) = lex_bytes(line, col + 2, ln, context)?;
+                        col = end;
+                        tokens[tln].push(Token(T::Bytes(bytes), Position::new(ln..ln+1, start..col)));
+                        continue
+                    }
+                    // raw string
+                    if line[col..].starts_with("r\"") {
+                        let start = col;
+                        col += 2;
+                        let mut parts: Vec<TemplatePart> = vec![];
+                        let (end, closed) = lex_string(path, line, col, ln, &RAW_QUOTE, &mut parts, context)?;
+                        col = end;
+                        if !closed {
+                            context.trace(Position::new(ln..ln+1, start..col));
+                            return Err(E::UnterminatedString)
+                        }
+                        tokens[tln].push(Token(string_token(parts), Position::new(ln..ln+1, start..col)));
+                        continue
+                    }
                     // id
-                    if LETTERS.contains(&&line[col..col+1]) {
+                    if c.is_alphabetic() || c == '_' {
                         let start = col;
                         let mut id = String::new();
-                        while col < line.len() {
-                            if !LETTERS.contains(&&line[col..col+1]) { break }
-                            id.push_str(&line[col..col+1]);
-                            col += 1;
+                        for c in line[col..].chars() {
+                            if !(c.is_alphanumeric() || c == '_') { break }
+                            id.push(c);
+                            col += c.len_utf8();
                         }
-                        tokens[ln].push(Token((|| match id.as_str() {
+                        tokens[tln].push(Token((|| match id.as_str() {
                             "_" => T::Wildcard,
                             "null" => T::Null,
                             "true" | "false" => T::Bool(id == "true"),
                             "rule" => T::Rule,
+                            "shape" => T::Shape,
                             "container" => T::Container,
                             "proc" => T::Proc,
                             "if" => T::If,
@@ -353,6 +633,7 @@ pub fn lex(path: &String, text: &String, context: &mut Context) -> Result<Vec<Ve
                             "return" => T::Return,
                             "break" => T::Break,
                             "continue" => T::Continue,
+                            "del" => T::Del,
                             "is" => T::Is,
                             "in" => T::Contains,
                             "or" => T::Or,
@@ -362,12 +643,17 @@ pub fn lex(path: &String, text: &String, context: &mut Context) -> Result<Vec<Ve
                             "any" => T::Type(Type::Any),
                             "undefined" => T::Type(Type::Undefined),
                             "int" => T::Type(Type::Int),
+                            "bigint" => T::Type(Type::BigInt),
                             "float" => T::Type(Type::Float),
                             "bool" => T::Type(Type::Bool),
                             "str" => T::Type(Type::String),
+                            "bytes" => T::Type(Type::Bytes),
                             "tuple" => T::Type(Type::Tuple(vec![])),
                             "vec" => T::Type(Type::Vector(vec![Type::Any])),
-                            "obj" => T::Type(Type::Object),
+                            "obj" => T::Type(Type::Object(vec![Type::Any])),
+                            "set" => T::Type(Type::Set(vec![Type::Any])),
+                            "range" => T::Type(Type::Range),
+                            "map" => T::Type(Type::Map(Box::new((vec![Type::Any], vec![Type::Any])))),
                             "type" => T::Type(Type::Type),
                             "union" => T::Type(Type::Union(vec![Type::Any])),
                             "scission" => T::Type(Type::Scission(vec![Type::Any])),
@@ -376,41 +662,93 @@ pub fn lex(path: &String, text: &String, context: &mut Context) -> Result<Vec<Ve
                         continue
                     }
                     // number
-                    if DIGITS.contains(&&line[col..col+1]) {
+                    if c.is_ascii_digit() {
                         let start = col;
+                        let radix = match line.get(col..col+2) {
+                            Some("0x" | "0X") => 16,
+                            Some("0b" | "0B") => 2,
+                            Some("0o" | "0O") => 8,
+                            _ => 10
+                        };
+                        if radix != 10 { col += 2 }
                         let mut number = String::new();
-                        while col < line.len() {
-                            if !DIGITS.contains(&&line[col..col+1]) { break }
-                            number.push_str(&line[col..col+1]);
-                            col += 1;
-                        }
-                        if col < line.len() {
-                            if &line[col..col+1] == "." {
-                                number.push_str(&line[col..col+1]);
+                        let mut float = false;
+                        while let Some(c) = line[col..].chars().next() {
+                            if c == '_' {
+                                col += 1;
+                                continue
+                            }
+                            if c.is_digit(radix) {
+                                number.push(c);
                                 col += 1;
-                                while col < line.len() {
-                                    if !DIGITS.contains(&&line[col..col+1]) { break }
-                                    number.push_str(&line[col..col+1]);
-                                    col += 1;
-                                }
-                                tokens[ln].push(Token(T::Float(number.parse().unwrap()), Position::new(ln..ln+1, start..col)));
                                 continue
                             }
+                            if radix != 10 { break }
+                            // a decimal point, but not the start of a `..`
+                            if c == '.' && !float && !line[col+1..].starts_with('.') {
+                                float = true;
+                                number.push(c);
+                                col += 1;
+                                continue
+                            }
+                            // an exponent like `e3` or `e-3`
+                            if c == 'e' || c == 'E' {
+                                let sign = if line[col+1..].starts_with(['+', '-']) { 1 } else { 0 };
+                                if line[col+1+sign..].starts_with(|c: char| c.is_ascii_digit()) {
+                                    float = true;
+                                    number.push_str(&line[col..col+1+sign]);
+                                    col += 1 + sign;
+                                    continue
+                                }
+                            }
+                            break
+                        }
+                        // letters or digits of another radix right after the digits like `0b102`, `0xfg` or `1e`
+                        let invalid = line[col..].starts_with(|c: char| c.is_alphanumeric() || c == '_');
+                        while line[col..].starts_with(|c: char| c.is_alphanumeric() || c == '_') {
+                            col += line[col..].chars().next().map_or(1, |c| c.len_utf8());
+                        }
+                        let pos = Position::new(ln..ln+1, start..col);
+                        if number.is_empty() || invalid {
+                            context.trace(pos);
+                            return Err(E::InvalidNumber(line[start..col].to_string()))
                         }
-                        tokens[ln].push(Token(T::Int(number.parse().unwrap()), Position::new(ln..ln+1, start..col)));
+                        let token = if float {
+                            match number.parse::<f64>() {
+                                Ok(v) if v.is_finite() => T::Float(v),
+                                _ => {
+                                    context.trace(pos);
+                                    return Err(E::NumberOverflow(line[start..col].to_string()))
+                                }
+                            }
+                        } else {
+                            match i64::from_str_radix(&number, radix) {
+                                Ok(v) => T::Int(v),
+                                Err(_) => {
+                                    context.trace(pos);
+                                    return Err(E::NumberOverflow(line[start..col].to_string()))
+                                }
+                            }
+                        };
+                        tokens[tln].push(Token(token, pos));
                         continue
                     }
                     // error
-                    context.trace(Position::new(ln..ln+1, col..col+1));
-                    return Err(E::IllegalChar(line[col..col+1].to_string()))
+                    context.trace(Position::new(ln..ln+1, col..col+c.len_utf8()));
+                    return Err(E::IllegalChar(c.to_string()))
                 }
             }
         }
-        match tokens.get_mut(ln) {
+        if open_string.is_some() { continue }
+        match tokens.get_mut(tln) {
             Some(tokens_) => tokens_.push(Token(T::EOL, Position::new(ln..ln+1, col..col))),
             None => {},
         }
     }
+    if let Some((_, start_ln, start_col)) = open_string {
+        context.trace(Position::new(start_ln..start_ln+1, start_col..start_col+3));
+        return Err(E::UnterminatedString)
+    }
     tokens.push(vec![Token(T::EOF, Position::new(tokens.len()-1..tokens.len(), 0..0))]); // end of file
     Ok(tokens)
 }
\ No newline at end of file
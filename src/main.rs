@@ -1,6 +1,7 @@
 #![allow(dead_code)]#![allow(unused)]
 
 use std::thread;
+use std::io::Write;
 
 mod position;
 mod errors;
@@ -9,6 +10,18 @@ mod context;
 mod lexer;
 mod parser;
 mod inter;
+mod json;
+mod bigint;
+mod math;
+mod random;
+mod time;
+mod sys;
+mod http;
+mod net;
+mod bytes;
+mod vector;
+mod object;
+mod ordered_map;
 use position::*;
 use errors::*;
 use value::*;
@@ -16,6 +29,18 @@ use context::*;
 use lexer::*;
 use parser::*;
 use inter::*;
+use json::*;
+use bigint::*;
+use math::*;
+use random::*;
+use time::*;
+use sys::*;
+use http::*;
+use net::*;
+use bytes::*;
+use vector::*;
+use object::*;
+use ordered_map::*;
 
 fn run(path: &String, text: &String, context: &mut Context) -> Result<Option<V>, E> {
     let tokens = lex(path, text, context)?;
@@ -24,7 +49,7 @@ fn run(path: &String, text: &String, context: &mut Context) -> Result<Option<V>,
     //     for token in line.iter() { print!("{token:?}\t"); }
     //     println!();
     // }
-    let ast = parse(path, tokens, context)?;
+    let ast = parse(path, text, tokens, context)?;
     // println!("{ast}");
     // println!("{}", ast.display(0));
     let (value, ret) = interpret(&ast, context)?;
@@ -34,17 +59,22 @@ fn run(path: &String, text: &String, context: &mut Context) -> Result<Option<V>,
     Ok(None)
 }
 fn run_context(path: &String, text: &String) -> Result<Option<V>, (E, Trace)> {
+    run_context_args(path, text, &[])
+}
+/// runs a program in a new context where `sys.args` are `args`
+fn run_context_args(path: &String, text: &String, args: &[String]) -> Result<Option<V>, (E, Trace)> {
     let mut context = Context::new(path);
     std_context(&mut context);
+    set_args(&mut context, args);
     let res = run(path, text, &mut context);
     match res {
         Ok(v) => Ok(v),
         Err(e) => Err((e, context.trace))
     }
 }
-fn run_file_context(path: &String) -> Result<Option<V>, (E, Trace)> {
+fn run_file_context(path: &String, args: &[String]) -> Result<Option<V>, (E, Trace)> {
     match std::fs::read_to_string(path.as_str()) {
-        Ok(text) => run_context(path, &text),
+        Ok(text) => run_context_args(path, &text, args),
         Err(e) => Err((E::TargetFile(path.clone()), vec![])),
     }
 }
@@ -57,14 +87,18 @@ fn run_file(path: &String, context: &mut Context) -> Result<Option<V>, E> {
 
 fn _main() {
     let mut args_ = std::env::args().collect::<Vec<String>>();
-    let mut args = args_.iter_mut();
+    let mut args = args_.iter();
     args.next();
     match args.next() {
-        Some(path) => match run_file_context(path) {
+        Some(path) => match run_file_context(path, &args_[2..]) {
             Ok(v) => match v {
                 Some(v) => println!("{v}"),
                 None => {}
             }
+            Err((E::Exit(code), _)) => {
+                std::io::stdout().flush();
+                std::process::exit(code)
+            }
             Err((e, trace)) => println!("{e}\n{}", display_trace(trace)),
         }
         None => return,
@@ -105,7 +139,7 @@ mod tests {
         }
     }
     fn do_file(path: &'static str) -> Result<(), E> {
-        match run_file_context(&path.to_string()) {
+        match run_file_context(&path.to_string(), &[]) {
             Ok(_) => Ok(()),
             Err((e, trace)) => Err(e)
         }
@@ -123,6 +157,11 @@ mod tests {
         assert!(Type::Scission(vec![Type::Int, Type::Float]) != Type::Int);
         assert!(Type::Vector(vec![Type::Int]) == Type::Vector(vec![Type::Int]));
         assert!(Type::Vector(vec![Type::Int, Type::Float]) == Type::Vector(vec![Type::Float, Type::Int]));
+        assert!(Type::RuleAnd(vec![Type::Rule("even".into()), Type::Int]) == Type::RuleAnd(vec![Type::Int, Type::Rule("even".into())]));
+        assert!(Type::RuleAnd(vec![Type::Rule("even".into()), Type::Int]) != Type::RuleOr(vec![Type::Rule("even".into()), Type::Int]));
+        assert!(Type::RuleOr(vec![Type::Rule("even".into()), Type::Int]) != Type::RuleOr(vec![Type::Rule("odd".into()), Type::Int]));
+        assert!(Type::RuleNot(Box::new(Type::Rule("even".into()))) == Type::RuleNot(Box::new(Type::Rule("even".into()))));
+        assert!(Type::RuleNot(Box::new(Type::Rule("even".into()))) != Type::Rule("even".into()));
     }
     #[test]
     fn samples_numbers() -> Result<(), E> {
@@ -141,4 +180,460 @@ mod tests {
     fn samples_person() -> Result<(), E> {
         do_file("samples/person.pr")
     }
+    #[test]
+    fn samples_json() -> Result<(), E> {
+        test_file("samples/json.pr")
+    }
+    #[test]
+    fn samples_shape() -> Result<(), E> {
+        test_file("samples/shape.pr")
+    }
+    #[test]
+    fn samples_rules() -> Result<(), E> {
+        test_file("samples/rules.pr")
+    }
+    #[test]
+    fn samples_types() -> Result<(), E> {
+        test_file("samples/types.pr")
+    }
+    #[test]
+    fn element_types() {
+        assert!(matches!(run_context(&"<TEST>".into(), &"proc f <- x: vec[int]\n    return x\nf! [1, 2, \"a\"]\n".into()),
+            Err((E::ExpectedElement(path, Type::Int, Type::String), _)) if path == "[2]"));
+        assert!(matches!(run_context(&"<TEST>".into(), &"proc f <- x: vec[vec[int]]!\n    return x\nf! [[1], [\"2\", \"a\"]]\n".into()),
+            Err((E::CastElement(path, Type::Int, V::String(_)), _)) if path == "[1][1]"));
+        assert!(matches!(run_context(&"<TEST>".into(), &"proc f <- x: obj[str]\n    return x\nf! { a = \"1\", b = 2 }\n".into()),
+            Err((E::ExpectedElement(path, Type::String, Type::Int), _)) if path == ".b"));
+        assert!(matches!(run_context(&"<TEST>".into(), &"proc f <- x: (int, str)\n    return x\nf! (1, 2)\n".into()),
+            Err((E::ExpectedElement(path, Type::String, Type::Int), _)) if path == "[1]"));
+    }
+    #[test]
+    fn samples_strings() -> Result<(), E> {
+        test_file("samples/strings.pr")
+    }
+    #[test]
+    fn string_errors() {
+        assert!(matches!(run_context(&"<TEST>".into(), &"var s = \"abc\n".into()), Err((E::UnterminatedString, _))));
+        assert!(matches!(run_context(&"<TEST>".into(), &"var s = \"\"\"abc\n\n".into()), Err((E::UnterminatedString, _))));
+        assert!(matches!(run_context(&"<TEST>".into(), &"var s = r\"abc\n".into()), Err((E::UnterminatedString, _))));
+        assert!(matches!(run_context(&"<TEST>".into(), &"var s = \"a\\qb\"\n".into()), Err((E::InvalidEscape(_), _))));
+        assert!(matches!(run_context(&"<TEST>".into(), &"var s = \"\\u{110000}\"\n".into()), Err((E::InvalidEscape(_), _))));
+        match run_context(&"<TEST>".into(), &"var s = \"\"\"a\nb\nc\"\"\"\nvar t = 1\nprint! t + \"x\"\n".into()) {
+            Err((E::Binary(T::Add, _, _), trace)) => assert_eq!(trace.last().map(|(pos, _)| (pos.0.start, pos.1.start)), Some((4, 7))),
+            _ => panic!("expected binary error")
+        }
+        match run_context(&"<TEST>".into(), &"var s = \"\"\"a\n\n\"\"\"\n\nvar = 2\n".into()) {
+            Err((E::UnexpectedToken(_), trace)) => assert_eq!(trace.last().map(|(pos, _)| pos.0.start), Some(4)),
+            _ => panic!("expected unexpected token error")
+        }
+    }
+    #[test]
+    fn multi_line_string_crlf() {
+        let res = run_context(&"<TEST>".into(), &"var s = \"\"\"a\r\n  b\r\n\"\"\"\r\nreturn s\r\n".into()).map_err(|(e, _)| e.to_string());
+        assert_eq!(res, Ok(Some(V::String("a\n  b\n".into()))));
+    }
+    #[test]
+    fn template_errors() {
+        match run_context(&"<TEST>".into(), &"var x = \"value: {1 + [2]}\"\n".into()) {
+            Err((E::Binary(T::Add, _, _), trace)) => assert_eq!(trace.last().map(|(pos, _)| (pos.0.start, pos.1.start)), Some((0, 17))),
+            _ => panic!("expected binary error")
+        }
+        match run_context(&"<TEST>".into(), &"var x = \"value: {1 $ 2}\"\n".into()) {
+            Err((E::IllegalChar(_), trace)) => assert_eq!(trace.last().map(|(pos, _)| (pos.0.start, pos.1.start)), Some((0, 19))),
+            _ => panic!("expected illegal character error")
+        }
+        // a `{` without an expression and a `}` after it is just text
+        for text in ["{", "{}", "{ }", "a { b", "{1 + 2", "} {"] {
+            let res = run_context(&"<TEST>".into(), &format!("return \"{text}\"\n")).map_err(|(e, _)| e.to_string());
+            assert_eq!(res, Ok(Some(V::String(text.into()))));
+        }
+    }
+    #[test]
+    fn samples_str() -> Result<(), E> {
+        test_file("samples/str.pr")
+    }
+    #[test]
+    fn str_format_errors() {
+        assert!(matches!(run_context(&"<TEST>".into(), &"str.format! '{} {}', 1\n".into()), Err((E::IndexRange(1, 1), _))));
+        assert!(matches!(run_context(&"<TEST>".into(), &"str.format! '{a}', 1\n".into()), Err((E::Format(_), _))));
+        assert!(matches!(run_context(&"<TEST>".into(), &"str.format! 'a}', 1\n".into()), Err((E::Format(_), _))));
+        assert!(matches!(run_context(&"<TEST>".into(), &"\"a\".nothing! 1\n".into()), Err((E::FieldNotFound(_), _))));
+    }
+    #[test]
+    fn samples_unicode() -> Result<(), E> {
+        test_file("samples/unicode.pr")
+    }
+    #[test]
+    fn char_indexing() {
+        assert!(matches!(run_context(&"<TEST>".into(), &"print! \"äb\"[-3]\n".into()), Err((E::IndexRange(2, -3), _))));
+        assert!(matches!(run_context(&"<TEST>".into(), &"print! [1, 2][2]\n".into()), Err((E::IndexRange(2, 2), _))));
+        match run_context(&"<TEST>".into(), &"var ä = 1 § 2\n".into()) {
+            Err((E::IllegalChar(c), trace)) => {
+                assert_eq!(c, "§");
+                assert_eq!(trace.last().map(|(pos, _)| pos.1.clone()), Some(11..13));
+            }
+            _ => panic!("expected illegal character error")
+        }
+    }
+    #[test]
+    fn samples_literals() -> Result<(), E> {
+        test_file("samples/literals.pr")
+    }
+    #[test]
+    fn number_errors() {
+        match run_context(&"<TEST>".into(), &"var x = 1 + 99999999999999999999\n".into()) {
+            Err((E::NumberOverflow(_), trace)) => assert_eq!(trace.last().map(|(pos, _)| pos.1.clone()), Some(12..32)),
+            _ => panic!("expected overflow error")
+        }
+        assert!(matches!(run_context(&"<TEST>".into(), &"var x = 1e400\n".into()), Err((E::NumberOverflow(_), _))));
+        assert!(matches!(run_context(&"<TEST>".into(), &"var x = 0b\n".into()), Err((E::InvalidNumber(_), _))));
+        assert!(matches!(run_context(&"<TEST>".into(), &"var x = 0b102\n".into()), Err((E::InvalidNumber(s), _)) if s == "0b102"));
+        assert!(matches!(run_context(&"<TEST>".into(), &"var x = 0xfg\n".into()), Err((E::InvalidNumber(s), _)) if s == "0xfg"));
+        assert!(matches!(run_context(&"<TEST>".into(), &"var x = 1e\n".into()), Err((E::InvalidNumber(s), _)) if s == "1e"));
+        assert!(matches!(run_context(&"<TEST>".into(), &"var x = 12abc\n".into()), Err((E::InvalidNumber(s), _)) if s == "12abc"));
+    }
+    #[test]
+    fn samples_bigint() -> Result<(), E> {
+        test_file("samples/bigint.pr")
+    }
+    #[test]
+    fn samples_math() -> Result<(), E> {
+        test_file("samples/math.pr")
+    }
+    #[test]
+    fn samples_bits() -> Result<(), E> {
+        test_file("samples/bits.pr")
+    }
+    #[test]
+    fn samples_random() -> Result<(), E> {
+        test_file("samples/random.pr")
+    }
+    #[test]
+    fn random_errors() {
+        assert!(matches!(run_context(&"<TEST>".into(), &"print! random.int(2, 1)\n".into()), Err((E::EmptyRange(2, 1), _))));
+        assert!(matches!(run_context(&"<TEST>".into(), &"print! random.choice([])\n".into()), Err((E::IndexRange(0, 0), _))));
+    }
+    #[test]
+    fn samples_time() -> Result<(), E> {
+        test_file("samples/time.pr")
+    }
+    #[test]
+    fn date_errors() {
+        assert!(matches!(run_context(&"<TEST>".into(), &"print! time.parse(\"2023-02-29\")\n".into()), Err((E::Date(_), _))));
+        assert!(matches!(run_context(&"<TEST>".into(), &"print! time.parse(\"12:30\")\n".into()), Err((E::Date(_), _))));
+        assert!(matches!(run_context(&"<TEST>".into(), &"print! time.timestamp({ year = 2024, month = 13 })\n".into()), Err((E::Date(_), _))));
+        assert!(matches!(run_context(&"<TEST>".into(), &"print! time.format(math.inf)\n".into()), Err((E::Date(_), _))));
+        assert!(matches!(run_context(&"<TEST>".into(), &"print! time.timestamp({ year = 9223372036854775807 })\n".into()), Err((E::Date(_), _))));
+        assert!(matches!(run_context(&"<TEST>".into(), &"print! time.timestamp({ year = -9223372036854775807 })\n".into()), Err((E::Date(_), _))));
+    }
+    #[test]
+    fn samples_sys() -> Result<(), E> {
+        test_file("samples/sys.pr")
+    }
+    #[test]
+    fn sys_errors() {
+        assert!(matches!(run_context(&"<TEST>".into(), &"sys.exit! 3\nprint! 1\n".into()), Err((E::Exit(3), _))));
+        assert!(matches!(run_context(&"<TEST>".into(), &"sys.exit!\n".into()), Err((E::Exit(0), _))));
+        assert!(matches!(run_context(&"<TEST>".into(), &"sys.exit! 4294967296\n".into()), Err((E::Error(_), _))));
+        assert!(matches!(run_context(&"<TEST>".into(), &"print! sys.run(\"proc-missing-program\")\n".into()), Err((E::Process(_, _), _))));
+    }
+    /// answers a single request on 127.0.0.1 with `response`, the handle returns the request it got
+    fn stand_in_server(response: &'static str) -> (u16, thread::JoinHandle<String>) {
+        use std::io::{BufRead, BufReader, Read};
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let handle = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let (mut request, mut length) = (String::new(), 0);
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if let Some(value) = line.to_lowercase().strip_prefix("content-length:") {
+                    length = value.trim().parse().unwrap();
+                }
+                request.push_str(&line);
+                if line == "\r\n" { break }
+            }
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body).unwrap();
+            request.push_str(&String::from_utf8(body).unwrap());
+            stream.write_all(response.as_bytes()).unwrap();
+            request
+        });
+        (port, handle)
+    }
+    #[test]
+    fn http_requests() {
+        let (port, server) = stand_in_server("HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nContent-Length: 5\r\n\r\nhello");
+        let text = format!("var r = http.get(\"http://127.0.0.1:{port}/greet?x=1\", {{ accept = \"text/plain\" }})\nreturn (r.status, \"content-type\" in r.headers, r.body)\n");
+        let res = run_context(&"<TEST>".into(), &text).map_err(|(e, _)| e.to_string());
+        assert_eq!(res, Ok(Some(V::Tuple(vec![V::Int(200), V::Bool(true), V::String("hello".into())]))));
+        let request = server.join().unwrap();
+        assert!(request.starts_with("GET /greet?x=1 HTTP/1.1\r\n"));
+        assert!(request.contains("accept: text/plain\r\n"));
+
+        let (port, server) = stand_in_server("HTTP/1.1 201 Created\r\nTransfer-Encoding: chunked\r\n\r\n3\r\nabc\r\n2\r\nde\r\n0\r\n\r\n");
+        let text = format!("var r = http.post(\"http://127.0.0.1:{port}/items\", {{ id = 1 }})\nreturn (r.status, r.body)\n");
+        let res = run_context(&"<TEST>".into(), &text).map_err(|(e, _)| e.to_string());
+        assert_eq!(res, Ok(Some(V::Tuple(vec![V::Int(201), V::String("abcde".into())]))));
+        let request = server.join().unwrap();
+        assert!(request.starts_with("POST /items HTTP/1.1\r\n"));
+        assert!(request.contains("Content-Type: application/json\r\n"));
+        assert!(request.ends_with("\r\n\r\n{\"id\":1}"));
+    }
+    /// answers every request with `response` and keeps what was sent, without a network
+    struct StubTransport {
+        sent: Vec<(String, u16, String)>,
+        response: &'static str
+    }
+    impl Transport for StubTransport {
+        fn send(&mut self, host: &str, port: u16, request: &[u8]) -> std::io::Result<Vec<u8>> {
+            self.sent.push((host.into(), port, String::from_utf8_lossy(request).to_string()));
+            Ok(self.response.as_bytes().to_vec())
+        }
+    }
+    #[test]
+    fn http_stub_transport() {
+        let mut transport = StubTransport { sent: vec![], response: "HTTP/1.1 404 Not Found\r\nContent-Length: 4\r\n\r\ngone" };
+        let mut headers: OrderedMap<String, V> = OrderedMap::new();
+        headers.insert("x-id".into(), V::Int(7));
+        let response = request(&mut transport, "DELETE", "http://example.test/items/7", &V::Null, &headers);
+        match response {
+            Ok(V::Object(response)) => {
+                assert_eq!(response.get("status"), Some(&V::Int(404)));
+                assert_eq!(response.get("body"), Some(&V::String("gone".into())));
+            }
+            _ => panic!("expected a response object")
+        }
+        let (host, port, sent) = &transport.sent[0];
+        assert_eq!((host.as_str(), *port), ("example.test", 80));
+        assert!(sent.starts_with("DELETE /items/7 HTTP/1.1\r\nHost: example.test\r\n"));
+        assert!(sent.ends_with("x-id: 7\r\n\r\n"));
+    }
+    #[test]
+    fn http_errors() {
+        assert!(matches!(run_context(&"<TEST>".into(), &"print! http.get(\"https://example.com\")\n".into()), Err((E::Http(_), _))));
+        assert!(matches!(run_context(&"<TEST>".into(), &"print! http.get(\"http://127.0.0.1:x/\")\n".into()), Err((E::Http(_), _))));
+        assert!(matches!(run_context(&"<TEST>".into(), &"print! http.get(\"http://127.0.0.1:9/\", { x = \"a\\r\\nInjected: b\" })\n".into()),
+            Err((E::Http(e), _)) if e.contains("line break")));
+        assert!(matches!(parse_response(b"garbage"), Err(E::Http(_))));
+    }
+    #[test]
+    fn samples_net() -> Result<(), E> {
+        test_file("samples/net.pr")
+    }
+    #[test]
+    fn net_send_bytes() {
+        use std::io::Read;
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let text = format!("var s = net.connect(\"{}\")\nreturn net.send(s, b\"\\x00\\xff\")\n", listener.local_addr().unwrap());
+        let res = run_context(&"<TEST>".into(), &text).map_err(|(e, _)| e.to_string());
+        assert_eq!(res, Ok(Some(V::Int(2))));
+        let mut received = vec![];
+        listener.accept().unwrap().0.read_to_end(&mut received).unwrap();
+        assert_eq!(received, vec![0x00, 0xff]);
+
+        let udp = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        let text = format!("var s = net.bind(\"127.0.0.1:0\")\nreturn net.send_to(s, b\"\\x00\\xff\", \"{}\")\n", udp.local_addr().unwrap());
+        let res = run_context(&"<TEST>".into(), &text).map_err(|(e, _)| e.to_string());
+        assert_eq!(res, Ok(Some(V::Int(2))));
+        let mut buffer = [0; 8];
+        let (n, _) = udp.recv_from(&mut buffer).unwrap();
+        assert_eq!(&buffer[..n], &[0x00, 0xff]);
+    }
+    #[test]
+    fn net_errors() {
+        assert!(matches!(run_context(&"<TEST>".into(), &"print! net.listen(\"not an address\")\n".into()), Err((E::Socket(_), _))));
+        assert!(matches!(run_context(&"<TEST>".into(), &"var s = net.listen(\"127.0.0.1:0\")\nprint! net.recv(s)\n".into()), Err((E::Socket(_), _))));
+        assert!(matches!(run_context(&"<TEST>".into(), &"var s = net.bind(\"127.0.0.1:0\")\nnet.close! s\nnet.send_to! s, \"x\", \"127.0.0.1:9\"\n".into()), Err((E::Socket(_), _))));
+        assert!(matches!(run_context(&"<TEST>".into(), &"print! net.send(1, \"x\")\n".into()), Err((E::ExpectedTypeArg(_, Type::Socket, Type::Int), _))));
+    }
+    #[test]
+    fn samples_bytes() -> Result<(), E> {
+        test_file("samples/bytes.pr")
+    }
+    #[test]
+    fn samples_prelude() -> Result<(), E> {
+        test_file("samples/prelude.pr")
+    }
+    #[test]
+    fn samples_vec() -> Result<(), E> {
+        test_file("samples/vec.pr")
+    }
+    #[test]
+    fn samples_obj() -> Result<(), E> {
+        test_file("samples/obj.pr")
+    }
+    #[test]
+    fn samples_collections() -> Result<(), E> {
+        test_file("samples/collections.pr")
+    }
+    #[test]
+    fn samples_range() -> Result<(), E> {
+        test_file("samples/range.pr")
+    }
+    #[test]
+    fn bytes_encodings() {
+        for (data, encoded) in [("", ""), ("a", "YQ=="), ("ab", "YWI="), ("abc", "YWJj"), ("abcd", "YWJjZA==")] {
+            assert_eq!(base64_encode(data.as_bytes()), encoded);
+            assert_eq!(base64_decode(encoded), Some(data.as_bytes().to_vec()));
+        }
+        assert_eq!(base64_decode("YQ=a"), None);
+        assert_eq!(hex_decode("0aff"), Some(vec![10, 255]));
+        assert_eq!(hex_decode("0af"), None);
+    }
+    #[test]
+    fn bytes_errors() {
+        assert!(matches!(run_context(&"<TEST>".into(), &"print! bytes.from_hex(\"zz\")\n".into()), Err((E::Decode(_), _))));
+        assert!(matches!(run_context(&"<TEST>".into(), &"print! bytes.from_base64(\"a\")\n".into()), Err((E::Decode(_), _))));
+        assert!(matches!(run_context(&"<TEST>".into(), &"var b = b\"\\xff\"\nprint! b.decode()\n".into()), Err((E::Decode(_), _))));
+        assert!(matches!(run_context(&"<TEST>".into(), &"print! b\"abc\n".into()), Err((E::UnterminatedString, _))));
+        assert!(matches!(run_context(&"<TEST>".into(), &"print! b\"\\xzz\"\n".into()), Err((E::InvalidEscape(_), _))));
+        assert!(matches!(run_context(&"<TEST>".into(), &"print! b\"a\"[1]\n".into()), Err((E::IndexRange(1, 1), _))));
+        assert!(matches!(run_context(&"<TEST>".into(), &"var b = b\"a\"\nb[0] = 256\n".into()), Err((E::Cast(Type::Bytes, _), _))));
+        assert!(matches!(run_context(&"<TEST>".into(), &"print! bytes([1, 300])\n".into()), Err((E::Cast(Type::Bytes, _), _))));
+    }
+    #[test]
+    fn vec_errors() {
+        let text = "proc positive <- x: int\n    assert! x > 0\n    return x\n";
+        assert!(matches!(run_context(&"<TEST>".into(), &format!("{text}print! [1, -1].map(positive)\n")), Err((E::Assertion, _))));
+        assert!(matches!(run_context(&"<TEST>".into(), &format!("{text}print! [1, \"a\"].map(positive)\n")), Err((E::ExpectedTypeArg(_, Type::Int, Type::String), _))));
+        assert!(matches!(run_context(&"<TEST>".into(), &"print! [1].map(2)\n".into()), Err((E::ExpectedTypeArg(_, _, Type::Int), _))));
+        assert!(matches!(run_context(&"<TEST>".into(), &"print! [1, \"a\"].sort()\n".into()), Err((E::Binary(T::LT, _, _), _))));
+        assert!(matches!(run_context(&"<TEST>".into(), &"print! [[1], [2]].sort()\n".into()), Err((E::Binary(T::LT, _, _), _))));
+        assert!(run_context(&"<TEST>".into(), &"assert! str([2.0, math.nan, 1, -1.5, math.nan, 0.5].sort()) == str([-1.5, 0.5, 1, 2.0, math.nan, math.nan])\n".into()).is_ok());
+    }
+    #[test]
+    fn ordered_map() {
+        let mut map: OrderedMap<String, i64> = OrderedMap::new();
+        for (k, v) in [("c", 1), ("a", 2), ("b", 3), ("a", 4)] {
+            map.insert(k.into(), v);
+        }
+        assert_eq!(map.remove("c"), Some(1));
+        assert_eq!(map.remove("c"), None);
+        map.insert("d".into(), 5);
+        assert_eq!(map.iter().map(|(k, v)| (k.as_str(), *v)).collect::<Vec<_>>(), vec![("a", 4), ("b", 3), ("d", 5)]);
+        assert_eq!(map.get("b"), Some(&3));
+        assert!(map == [("d".to_string(), 5), ("b".into(), 3), ("a".into(), 4)].into_iter().collect());
+    }
+    #[test]
+    fn obj_errors() {
+        assert!(matches!(run_context(&"<TEST>".into(), &"print! { (1) = 2 }\n".into()), Err((E::ExpectedType(Type::String, Type::Int), _))));
+        assert!(matches!(run_context(&"<TEST>".into(), &"var o = { a = 1 }\nprint! o[\"b\"]\n".into()), Err((E::FieldNotFound(_), _))));
+        assert!(matches!(run_context(&"<TEST>".into(), &"var o = {}\no[1] = 2\n".into()), Err((E::InvalidField(_, Type::Int), _))));
+        assert!(matches!(run_context(&"<TEST>".into(), &"var o = {}\ndel o.x\n".into()), Err((E::FieldNotFound(_), _))));
+        assert!(matches!(run_context(&"<TEST>".into(), &"var v = [1]\ndel v.x\n".into()), Err((E::InvalidHead(_), _))));
+        assert!(matches!(run_context(&"<TEST>".into(), &"var x = 1\ndel x\nprint! x\n".into()), Err((E::NotDefined(_), _))));
+    }
+    #[test]
+    fn collections_errors() {
+        assert!(matches!(run_context(&"<TEST>".into(), &"print! {1.5}\n".into()), Err((E::Unhashable(Type::Float), _))));
+        assert!(matches!(run_context(&"<TEST>".into(), &"print! {[1]: 2}\n".into()), Err((E::Unhashable(_), _))));
+        assert!(matches!(run_context(&"<TEST>".into(), &"var m = {1: 2}\nprint! m[3]\n".into()), Err((E::KeyNotFound(V::Int(3)), _))));
+        assert!(matches!(run_context(&"<TEST>".into(), &"var m = map()\nm[[1]] = 2\n".into()), Err((E::Unhashable(_), _))));
+        assert!(matches!(run_context(&"<TEST>".into(), &"var m = {1: 2}\ndel m[2]\n".into()), Err((E::KeyNotFound(_), _))));
+        assert!(matches!(run_context(&"<TEST>".into(), &"print! {1} | [2]\n".into()), Err((E::Binary(T::Option, _, _), _))));
+    }
+    #[test]
+    fn range_errors() {
+        assert!(matches!(run_context(&"<TEST>".into(), &"print! 0..5..0\n".into()), Err((E::RangeStep, _))));
+        assert!(matches!(run_context(&"<TEST>".into(), &"for i = 0, 5, 0\n    print! i\n".into()), Err((E::RangeStep, _))));
+        assert!(matches!(run_context(&"<TEST>".into(), &"print! 0..\"a\"\n".into()), Err((E::ExpectedType(Type::Int, Type::String), _))));
+        assert!(matches!(run_context(&"<TEST>".into(), &"print! 1.5..3\n".into()), Err((E::ExpectedType(Type::Int, Type::Float), _))));
+        assert!(matches!(run_context(&"<TEST>".into(), &"print! { a = 1 }[0..1]\n".into()), Err((E::InvalidField(_, Type::Range), _))));
+        assert!(matches!(run_context(&"<TEST>".into(), &"print! #(-9223372036854775807..9223372036854775807)\n".into()), Err((E::Overflow(T::Sub, _, _), _))));
+        assert!(matches!(run_context(&"<TEST>".into(), &"print! 0..=9223372036854775807\n".into()), Err((E::Overflow(T::Add, _, _), _))));
+        assert!(matches!(run_context(&"<TEST>".into(), &"print! vec(0..9223372036854775807)\n".into()), Err((E::Cast(_, V::Range(_, _, _)), _))));
+    }
+    #[test]
+    fn int_arithmetic_errors() {
+        assert!(matches!(run_context(&"<TEST>".into(), &"print! 9223372036854775807 + 1\n".into()), Err((E::Overflow(T::Add, _, _), _))));
+        assert!(matches!(run_context(&"<TEST>".into(), &"print! 4611686018427387904 * 2\n".into()), Err((E::Overflow(T::Mul, _, _), _))));
+        assert!(matches!(run_context(&"<TEST>".into(), &"print! -9223372036854775807 - 2\n".into()), Err((E::Overflow(T::Sub, _, _), _))));
+        assert!(matches!(run_context(&"<TEST>".into(), &"print! 1 / 0\n".into()), Err((E::DivisionByZero, _))));
+        assert!(matches!(run_context(&"<TEST>".into(), &"print! 1 % 0\n".into()), Err((E::DivisionByZero, _))));
+        assert!(matches!(run_context(&"<TEST>".into(), &"print! bigint(1) % 0\n".into()), Err((E::DivisionByZero, _))));
+        assert!(matches!(run_context(&"<TEST>".into(), &"print! 2 ^ 63\n".into()), Err((E::Overflow(T::Pow, _, _), _))));
+        assert!(matches!(run_context(&"<TEST>".into(), &"print! 1 << 64\n".into()), Err((E::Overflow(T::Shl, _, _), _))));
+        assert!(matches!(run_context(&"<TEST>".into(), &"print! 1 >> -1\n".into()), Err((E::Overflow(T::Shr, _, _), _))));
+        assert!(matches!(run_context(&"<TEST>".into(), &"print! 1 & 1.0\n".into()), Err((E::Binary(T::BitAnd, _, _), _))));
+        assert!(matches!(run_context(&"<TEST>".into(), &"print! 1 | 2 | int\n".into()), Err((E::Binary(T::Option, _, _), _))));
+        assert!(matches!(run_context(&"<TEST>".into(), &"print! math.div(1, 0)\n".into()), Err((E::DivisionByZero, _))));
+        assert!(matches!(run_context(&"<TEST>".into(), &"print! math.floor(math.inf)\n".into()), Err((E::Cast(Type::Int, _), _))));
+        assert!(matches!(run_context(&"<TEST>".into(), &"print! int(bigint(\"99999999999999999999\"))\n".into()), Err((E::Cast(Type::Int, _), _))));
+    }
+    #[test]
+    fn rule_params() {
+        let text = "rule between <- x, lo, hi\n    x >= lo\n    x <= hi\n";
+        assert!(matches!(run_context(&"<TEST>".into(), &format!("{text}print! between(1)\n")),
+            Err((E::RuleParams(_, 2, 1), _))));
+        assert!(matches!(run_context(&"<TEST>".into(), &format!("{text}assert! 1 is between(0, 2)\n")),
+            Ok(None)));
+    }
+    #[test]
+    fn rule_failure_report() {
+        let text = "rule number <- x\n    x is int\n    x >= 0\nrule natural <- x\n    x is number\n    x != 0\n\
+        proc f <- n: natural\n    return n\nf! -3\n";
+        match run_context(&"<TEST>".into(), &text.into()) {
+            Err((E::Rule(V::Int(-3), chain, Some(clause)), _)) => {
+                assert_eq!(chain, vec!["natural".to_string(), "number".to_string()]);
+                assert_eq!(clause.source, "x >= 0");
+                assert_eq!((clause.pos.0.start, clause.pos.1.start), (2, 4));
+                assert_eq!(clause.id, "x");
+                assert!(matches!(clause.value, V::Int(-3)));
+            }
+            _ => panic!("expected rule error")
+        }
+    }
+    #[test]
+    fn rule_failure_evaluates_once() {
+        let text = "proc bump <- x\n    sys.set_env! \"PROC_RULE_CALLS\", sys.env(\"PROC_RULE_CALLS\") + \"x\"\n    return x\n\
+        rule small <- x\n    x < 10\nrule wrapped <- x\n    bump(x) is small\nproc f <- n: wrapped\n    return n\nf! 30\n";
+        std::env::set_var("PROC_RULE_CALLS", "");
+        match run_context(&"<TEST>".into(), &text.into()) {
+            Err((E::Rule(V::Int(30), chain, Some(clause)), _)) => {
+                assert_eq!(chain, vec!["wrapped".to_string(), "small".to_string()]);
+                assert_eq!(clause.source, "x < 10");
+            }
+            _ => panic!("expected rule error")
+        }
+        assert_eq!(std::env::var("PROC_RULE_CALLS").as_deref(), Ok("x"));
+    }
+    #[test]
+    fn prelude_rules() {
+        match run_context(&"<TEST>".into(), &"proc f <- n: count\n    return n\nf! 0\n".into()) {
+            Err((E::Rule(V::Int(0), chain, Some(_)), trace)) => {
+                assert_eq!(chain, vec!["count".to_string()]);
+                assert_eq!(trace.last().map(|(_, path)| path.as_str()), Some("<PRELUDE>"));
+            }
+            _ => panic!("expected rule error")
+        }
+        assert!(matches!(run_context(&"<TEST>".into(), &"global count = int\nassert! 0 is count\n".into()), Ok(None)));
+        assert!(matches!(run_context(&"<TEST>".into(), &"global x = 1\nglobal x = 2\n".into()), Err((E::AlreadyDefined(_), _))));
+    }
+    #[test]
+    fn shape_failures() {
+        let text = "rule count <- x\n    x is int\n    x >= 0\nshape Person { name: str, age: count, job: str? }\n\
+        proc f <- person: Person\n    return person\nf! { age = -1, job = 2 }\n";
+        match run_context(&"<TEST>".into(), &text.into()) {
+            Err((E::Shape(name, failures), _)) => {
+                assert_eq!(name, "Person");
+                assert_eq!(failures, vec![
+                    "person.name: missing field".to_string(),
+                    "person.age: rule count does not apply".to_string(),
+                    "person.job: expected str, got int".to_string(),
+                ]);
+            }
+            _ => panic!("expected shape error")
+        }
+    }
+    #[test]
+    fn json_errors() {
+        assert!(matches!(parse_json("{\"a\": [1, 2}"), Err(E::JsonParse(_, 0, 11))));
+        assert!(matches!(parse_json("[1] 2"), Err(E::JsonParse(_, _, _))));
+        assert!(matches!(stringify_json(&V::Type(Type::Int), false, 0), Err(E::Unserializable(_))));
+        assert!(matches!(run_context(&"<TEST>".into(), &"proc f\n    return 1\njson.stringify! f\n".into()),
+            Err((E::Unserializable(_), _))));
+    }
 }
\ No newline at end of file
@@ -0,0 +1,136 @@
+use crate::*;
+
+fn arg(context: &Context, id: &str) -> V {
+    context.get(&id.to_string()).cloned().unwrap_or(V::Null)
+}
+fn arg_float(context: &Context, id: &str) -> f64 {
+    match context.get(&id.to_string()) {
+        Some(V::Int(v)) => *v as f64,
+        Some(V::Float(v)) => *v,
+        Some(V::BigInt(v)) => v.to_f64(),
+        _ => f64::NAN
+    }
+}
+/// `int`, `float` or `bigint`
+pub fn number_type() -> Type {
+    Type::create_union(vec![Type::Int, Type::Float, Type::BigInt])
+}
+
+pub fn math_abs(context: &mut Context, pos: &Position) -> Result<V, E> {
+    let x = arg(context, "x");
+    match &x {
+        V::Int(v) if *v < 0 => unary(&T::Sub, &x, pos, context),
+        V::Float(v) => Ok(V::Float(v.abs())),
+        V::BigInt(v) => Ok(V::BigInt(v.abs())),
+        _ => Ok(x)
+    }
+}
+/// rounds a `float` with `round` into an `int`, integers stay as they are
+fn round_with(context: &mut Context, pos: &Position, round: fn(f64) -> f64) -> Result<V, E> {
+    let x = arg(context, "x");
+    match &x {
+        V::Float(v) => {
+            let v = round(*v);
+            if v.is_finite() && v >= i64::MIN as f64 && v < i64::MAX as f64 {
+                Ok(V::Int(v as i64))
+            } else {
+                context.trace(pos.clone());
+                Err(E::Cast(Type::Int, x))
+            }
+        }
+        _ => Ok(x)
+    }
+}
+pub fn math_floor(context: &mut Context, pos: &Position) -> Result<V, E> {
+    round_with(context, pos, f64::floor)
+}
+pub fn math_ceil(context: &mut Context, pos: &Position) -> Result<V, E> {
+    round_with(context, pos, f64::ceil)
+}
+pub fn math_round(context: &mut Context, pos: &Position) -> Result<V, E> {
+    round_with(context, pos, f64::round)
+}
+pub fn math_sqrt(context: &mut Context, pos: &Position) -> Result<V, E> {
+    Ok(V::Float(arg_float(context, "x").sqrt()))
+}
+pub fn math_exp(context: &mut Context, pos: &Position) -> Result<V, E> {
+    Ok(V::Float(arg_float(context, "x").exp()))
+}
+/// the natural logarithm, or the logarithm to `base`
+pub fn math_log(context: &mut Context, pos: &Position) -> Result<V, E> {
+    let x = arg_float(context, "x");
+    match context.get(&String::from("base")) {
+        Some(V::Null) | None => Ok(V::Float(x.ln())),
+        _ => Ok(V::Float(x.log(arg_float(context, "base"))))
+    }
+}
+pub fn math_sin(context: &mut Context, pos: &Position) -> Result<V, E> {
+    Ok(V::Float(arg_float(context, "x").sin()))
+}
+pub fn math_cos(context: &mut Context, pos: &Position) -> Result<V, E> {
+    Ok(V::Float(arg_float(context, "x").cos()))
+}
+pub fn math_tan(context: &mut Context, pos: &Position) -> Result<V, E> {
+    Ok(V::Float(arg_float(context, "x").tan()))
+}
+pub fn math_asin(context: &mut Context, pos: &Position) -> Result<V, E> {
+    Ok(V::Float(arg_float(context, "x").asin()))
+}
+pub fn math_acos(context: &mut Context, pos: &Position) -> Result<V, E> {
+    Ok(V::Float(arg_float(context, "x").acos()))
+}
+pub fn math_atan(context: &mut Context, pos: &Position) -> Result<V, E> {
+    Ok(V::Float(arg_float(context, "x").atan()))
+}
+pub fn math_atan2(context: &mut Context, pos: &Position) -> Result<V, E> {
+    Ok(V::Float(arg_float(context, "y").atan2(arg_float(context, "x"))))
+}
+pub fn math_pow(context: &mut Context, pos: &Position) -> Result<V, E> {
+    binary(&T::Pow, &arg(context, "x"), &arg(context, "y"), pos, context)
+}
+/// the smallest (`op` is `<`) or biggest (`op` is `>`) value of a vector, `null` if it's empty
+fn extreme(context: &mut Context, pos: &Position, op: T) -> Result<V, E> {
+    let values = match arg(context, "values") {
+        V::Vector(values, _) => values,
+        _ => vec![]
+    };
+    let mut values = values.into_iter();
+    let mut result = match values.next() {
+        Some(v) => v,
+        None => return Ok(V::Null)
+    };
+    for v in values {
+        if binary(&op, &v, &result, pos, context)? == V::Bool(true) { result = v }
+    }
+    Ok(result)
+}
+pub fn math_min(context: &mut Context, pos: &Position) -> Result<V, E> {
+    extreme(context, pos, T::LT)
+}
+pub fn math_max(context: &mut Context, pos: &Position) -> Result<V, E> {
+    extreme(context, pos, T::GT)
+}
+pub fn math_clamp(context: &mut Context, pos: &Position) -> Result<V, E> {
+    let (x, lo, hi) = (arg(context, "x"), arg(context, "lo"), arg(context, "hi"));
+    if binary(&T::LT, &x, &lo, pos, context)? == V::Bool(true) { return Ok(lo) }
+    if binary(&T::GT, &x, &hi, pos, context)? == V::Bool(true) { return Ok(hi) }
+    Ok(x)
+}
+/// integer division rounding toward zero, so `div(a, b) * b + a % b == a`
+pub fn math_div(context: &mut Context, pos: &Position) -> Result<V, E> {
+    let (a, b) = (arg(context, "a"), arg(context, "b"));
+    match (&a, &b) {
+        (V::Int(_), V::Int(0)) => {
+            context.trace(pos.clone());
+            Err(E::DivisionByZero)
+        }
+        (V::Int(v1), V::Int(v2)) => match v1.checked_div(*v2) {
+            Some(v) => Ok(V::Int(v)),
+            None => {
+                context.trace(pos.clone());
+                Err(E::Overflow(T::Div, a.clone(), b.clone()))
+            }
+        }
+        _ => binary(&T::Div, &a, &b, pos, context)
+    }
+}
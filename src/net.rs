@@ -0,0 +1,165 @@
+use std::cell::RefCell;
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream, UdpSocket};
+use std::rc::Rc;
+use crate::*;
+
+/// the handle behind a `socket` value, closing it drops the OS socket
+pub enum Socket {
+    Listener(TcpListener), Stream(TcpStream), Udp(UdpSocket), Closed
+}
+impl Socket {
+    pub fn kind(&self) -> &str {
+        match self {
+            Self::Listener(_) => "tcp-listener",
+            Self::Stream(_) => "tcp",
+            Self::Udp(_) => "udp",
+            Self::Closed => "closed",
+        }
+    }
+    pub fn addr(&self) -> Option<String> {
+        match self {
+            Self::Listener(s) => s.local_addr().ok(),
+            Self::Stream(s) => s.peer_addr().ok(),
+            Self::Udp(s) => s.local_addr().ok(),
+            Self::Closed => None
+        }.map(|addr| addr.to_string())
+    }
+}
+impl std::fmt::Debug for Socket {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.addr() {
+            Some(addr) => write!(f, "socket:{}:{addr}", self.kind()),
+            None => write!(f, "socket:{}", self.kind())
+        }
+    }
+}
+
+fn socket_error(context: &mut Context, pos: &Position, e: impl std::fmt::Display) -> E {
+    context.trace(pos.clone());
+    E::Socket(e.to_string())
+}
+/// the bytes to send, a `str` is sent as UTF-8
+fn arg_data(context: &Context, id: &str) -> Vec<u8> {
+    match context.get(&id.to_string()) {
+        Some(V::Bytes(bytes)) => bytes.clone(),
+        Some(V::String(s)) => s.as_bytes().to_vec(),
+        _ => vec![]
+    }
+}
+fn arg_socket(context: &Context, id: &str) -> Rc<RefCell<Socket>> {
+    match context.get(&id.to_string()) {
+        Some(V::Socket(socket)) => socket.clone(),
+        _ => Rc::new(RefCell::new(Socket::Closed))
+    }
+}
+/// the size of the read buffer, `max` up to `limit` or 4096 bytes
+fn arg_max(context: &mut Context, pos: &Position, limit: usize) -> Result<usize, E> {
+    match context.get(&String::from("max")) {
+        Some(V::Int(max)) if *max > 0 => Ok((*max as u64).min(limit as u64) as usize),
+        Some(V::Int(max)) => {
+            let max = *max;
+            Err(socket_error(context, pos, format!("cannot receive {max} bytes")))
+        }
+        _ => Ok(4096)
+    }
+}
+fn new_socket(socket: Socket) -> V {
+    V::Socket(Rc::new(RefCell::new(socket)))
+}
+fn wrong_kind(context: &mut Context, pos: &Position, socket: &Socket, expected: &str) -> E {
+    socket_error(context, pos, format!("expected a {expected} socket, got {}", socket.kind()))
+}
+
+pub fn net_listen(context: &mut Context, pos: &Position) -> Result<V, E> {
+    match TcpListener::bind(arg_str(context, "addr")) {
+        Ok(listener) => Ok(new_socket(Socket::Listener(listener))),
+        Err(e) => Err(socket_error(context, pos, e))
+    }
+}
+pub fn net_accept(context: &mut Context, pos: &Position) -> Result<V, E> {
+    let socket = arg_socket(context, "socket");
+    let socket = socket.borrow();
+    match &*socket {
+        Socket::Listener(listener) => match listener.accept() {
+            Ok((stream, _)) => Ok(new_socket(Socket::Stream(stream))),
+            Err(e) => Err(socket_error(context, pos, e))
+        }
+        socket => Err(wrong_kind(context, pos, socket, "tcp-listener"))
+    }
+}
+pub fn net_connect(context: &mut Context, pos: &Position) -> Result<V, E> {
+    match TcpStream::connect(arg_str(context, "addr")) {
+        Ok(stream) => Ok(new_socket(Socket::Stream(stream))),
+        Err(e) => Err(socket_error(context, pos, e))
+    }
+}
+/// writes all of `data` and returns the number of bytes
+pub fn net_send(context: &mut Context, pos: &Position) -> Result<V, E> {
+    let (socket, data) = (arg_socket(context, "socket"), arg_data(context, "data"));
+    let mut socket = socket.borrow_mut();
+    match &mut *socket {
+        Socket::Stream(stream) => match stream.write_all(&data) {
+            Ok(()) => Ok(V::Int(data.len() as i64)),
+            Err(e) => Err(socket_error(context, pos, e))
+        }
+        socket => Err(wrong_kind(context, pos, socket, "tcp"))
+    }
+}
+/// reads up to `max` bytes (at most 64 KiB), an empty `str` means the other side closed the connection
+pub fn net_recv(context: &mut Context, pos: &Position) -> Result<V, E> {
+    let (socket, max) = (arg_socket(context, "socket"), arg_max(context, pos, 65536)?);
+    let mut socket = socket.borrow_mut();
+    match &mut *socket {
+        Socket::Stream(stream) => {
+            let mut buffer = vec![0; max];
+            match stream.read(&mut buffer) {
+                Ok(n) => Ok(V::String(String::from_utf8_lossy(&buffer[..n]).to_string())),
+                Err(e) => Err(socket_error(context, pos, e))
+            }
+        }
+        socket => Err(wrong_kind(context, pos, socket, "tcp"))
+    }
+}
+pub fn net_close(context: &mut Context, pos: &Position) -> Result<V, E> {
+    *arg_socket(context, "socket").borrow_mut() = Socket::Closed;
+    Ok(V::Null)
+}
+pub fn net_bind(context: &mut Context, pos: &Position) -> Result<V, E> {
+    match UdpSocket::bind(arg_str(context, "addr")) {
+        Ok(socket) => Ok(new_socket(Socket::Udp(socket))),
+        Err(e) => Err(socket_error(context, pos, e))
+    }
+}
+pub fn net_send_to(context: &mut Context, pos: &Position) -> Result<V, E> {
+    let (socket, data, addr) = (arg_socket(context, "socket"), arg_data(context, "data"), arg_str(context, "addr"));
+    let socket = socket.borrow();
+    match &*socket {
+        Socket::Udp(udp) => match udp.send_to(&data, addr) {
+            Ok(n) => Ok(V::Int(n as i64)),
+            Err(e) => Err(socket_error(context, pos, e))
+        }
+        socket => Err(wrong_kind(context, pos, socket, "udp"))
+    }
+}
+/// receives one datagram of up to `max` bytes as `(data, addr)`, no datagram is larger than 65507 bytes
+pub fn net_recv_from(context: &mut Context, pos: &Position) -> Result<V, E> {
+    let (socket, max) = (arg_socket(context, "socket"), arg_max(context, pos, 65507)?);
+    let socket = socket.borrow();
+    match &*socket {
+        Socket::Udp(udp) => {
+            let mut buffer = vec![0; max];
+            match udp.recv_from(&mut buffer) {
+                Ok((n, addr)) => Ok(V::Tuple(vec![
+                    V::String(String::from_utf8_lossy(&buffer[..n]).to_string()), V::String(addr.to_string())
+                ])),
+                Err(e) => Err(socket_error(context, pos, e))
+            }
+        }
+        socket => Err(wrong_kind(context, pos, socket, "udp"))
+    }
+}
+/// the address a socket is bound to, or for a connection the address of the other side
+pub fn net_addr(context: &mut Context, pos: &Position) -> Result<V, E> {
+    Ok(arg_socket(context, "socket").borrow().addr().map(V::String).unwrap_or(V::Null))
+}
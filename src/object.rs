@@ -0,0 +1,52 @@
+use crate::*;
+
+fn arg_obj(context: &Context, id: &str) -> OrderedMap<String, V> {
+    match context.get(&id.to_string()) {
+        Some(V::Object(obj)) => obj.clone(),
+        _ => OrderedMap::new()
+    }
+}
+fn arg_key(context: &Context) -> String {
+    match context.get(&String::from("key")) {
+        Some(V::String(key)) => key.clone(),
+        _ => String::new()
+    }
+}
+fn vector(values: Vec<V>) -> V {
+    let types = V::create_union(values.clone());
+    V::Vector(values, types)
+}
+
+pub fn obj_keys(context: &mut Context, pos: &Position) -> Result<V, E> {
+    Ok(vector(arg_obj(context, "self").into_keys().map(V::String).collect()))
+}
+pub fn obj_values(context: &mut Context, pos: &Position) -> Result<V, E> {
+    Ok(vector(arg_obj(context, "self").into_values().collect()))
+}
+/// the fields as `(key, value)` tuples
+pub fn obj_entries(context: &mut Context, pos: &Position) -> Result<V, E> {
+    let entries = arg_obj(context, "self").into_iter();
+    Ok(vector(entries.map(|(k, v)| V::Tuple(vec![V::String(k), v])).collect()))
+}
+pub fn obj_has(context: &mut Context, pos: &Position) -> Result<V, E> {
+    Ok(V::Bool(arg_obj(context, "self").contains_key(&arg_key(context))))
+}
+/// a copy without the field, which doesn't have to exist
+pub fn obj_remove(context: &mut Context, pos: &Position) -> Result<V, E> {
+    let mut obj = arg_obj(context, "self");
+    obj.remove(&arg_key(context));
+    Ok(V::Object(obj))
+}
+/// a copy with the fields of `other` added, replacing those with the same key
+pub fn obj_merge(context: &mut Context, pos: &Position) -> Result<V, E> {
+    let mut obj = arg_obj(context, "self");
+    obj.extend(arg_obj(context, "other"));
+    Ok(V::Object(obj))
+}
+/// the value of a field, or `default` (`null` if not given) if there is no such field
+pub fn obj_get(context: &mut Context, pos: &Position) -> Result<V, E> {
+    match arg_obj(context, "self").remove(&arg_key(context)) {
+        Some(value) => Ok(value),
+        None => Ok(context.get(&String::from("default")).cloned().unwrap_or(V::Null))
+    }
+}
@@ -0,0 +1,87 @@
+use std::{borrow::Borrow, collections::HashMap, hash::Hash};
+
+/// a map which keeps its entries in insertion order, with the lookup of a `HashMap`,
+/// replacing a value keeps its place and removing one shifts the later entries
+#[derive(Debug, Clone)]
+pub struct OrderedMap<K, V> {
+    indices: HashMap<K, usize>,
+    entries: Vec<(K, V)>
+}
+impl<K: Hash + Eq + Clone, V> OrderedMap<K, V> {
+    pub fn new() -> Self { Self { indices: HashMap::new(), entries: vec![] } }
+
+    pub fn len(&self) -> usize { self.entries.len() }
+    pub fn is_empty(&self) -> bool { self.entries.is_empty() }
+
+    pub fn get<Q: Hash + Eq + ?Sized>(&self, key: &Q) -> Option<&V> where K: Borrow<Q> {
+        self.indices.get(key).map(|i| &self.entries[*i].1)
+    }
+    pub fn get_mut<Q: Hash + Eq + ?Sized>(&mut self, key: &Q) -> Option<&mut V> where K: Borrow<Q> {
+        self.indices.get(key).map(|i| &mut self.entries[*i].1)
+    }
+    pub fn contains_key<Q: Hash + Eq + ?Sized>(&self, key: &Q) -> bool where K: Borrow<Q> {
+        self.indices.contains_key(key)
+    }
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        match self.indices.get(&key) {
+            Some(i) => Some(std::mem::replace(&mut self.entries[*i].1, value)),
+            None => {
+                self.indices.insert(key.clone(), self.entries.len());
+                self.entries.push((key, value));
+                None
+            }
+        }
+    }
+    pub fn remove<Q: Hash + Eq + ?Sized>(&mut self, key: &Q) -> Option<V> where K: Borrow<Q> {
+        let i = self.indices.remove(key)?;
+        let (_, value) = self.entries.remove(i);
+        for (key, _) in self.entries[i..].iter() {
+            if let Some(index) = self.indices.get_mut::<K>(key) { *index -= 1 }
+        }
+        Some(value)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.entries.iter().map(|(k, v)| (k, v))
+    }
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.entries.iter().map(|(k, _)| k)
+    }
+    pub fn values(&self) -> impl Iterator<Item = &V> {
+        self.entries.iter().map(|(_, v)| v)
+    }
+    pub fn into_keys(self) -> impl Iterator<Item = K> {
+        self.entries.into_iter().map(|(k, _)| k)
+    }
+    pub fn into_values(self) -> impl Iterator<Item = V> {
+        self.entries.into_iter().map(|(_, v)| v)
+    }
+}
+impl<K: Hash + Eq + Clone, V> Default for OrderedMap<K, V> {
+    fn default() -> Self { Self::new() }
+}
+/// two maps are equal if they have the same entries, in any order
+impl<K: Hash + Eq + Clone, V: PartialEq> PartialEq for OrderedMap<K, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().all(|(k, v)| other.get(k) == Some(v))
+    }
+}
+impl<K: Hash + Eq + Clone, V> IntoIterator for OrderedMap<K, V> {
+    type Item = (K, V);
+    type IntoIter = std::vec::IntoIter<(K, V)>;
+    fn into_iter(self) -> Self::IntoIter { self.entries.into_iter() }
+}
+impl<K: Hash + Eq + Clone, V> FromIterator<(K, V)> for OrderedMap<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = Self::new();
+        map.extend(iter);
+        map
+    }
+}
+impl<K: Hash + Eq + Clone, V> Extend<(K, V)> for OrderedMap<K, V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (k, v) in iter {
+            self.insert(k, v);
+        }
+    }
+}
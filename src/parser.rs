@@ -6,19 +6,22 @@ pub type ProcParams = Vec<(Node, Option<Node>, bool)>;
 #[derive(Debug, Clone)]
 pub enum N {
     Body(Vec<Node>),
-    Wildcard, Null, Int(i64), Float(f64), Bool(bool), String(String),
-    Vector(Vec<Node>), Tuple(Vec<Node>), Object(Vec<(Node, Node)>), ID(String), Type(Type),
+    Wildcard, Null, Int(i64), Float(f64), Bool(bool), String(String), Template(Vec<Node>), Bytes(Vec<u8>),
+    Vector(Vec<Node>), Tuple(Vec<Node>), Object(Vec<(Node, Node)>), Set(Vec<Node>), Map(Vec<(Node, Node)>),
+    ID(String), Type(Type),
     Binary { op: T, left: Box<Node>, right: Box<Node> },
     Unary { op: T, node: Box<Node> }, Multi { op: T, nodes: Vec<Node> },
+    Range { start: Box<Node>, end: Box<Node>, step: Option<Box<Node>>, inclusive: bool },
     Assign { global: bool, id: Box<Node>, expr: Box<Node> }, OpAssign { op: T, id: Box<Node>, expr: Box<Node> },
     Inc(Box<Node>), Dec(Box<Node>),
-    Return(Box<Node>), Break, Continue,
+    Return(Box<Node>), Break, Continue, Del(Box<Node>),
     Call { id: Box<Node>, args: Vec<Node> }, CallExpr { id: Box<Node>, args: Vec<Node> },
     If { cond: Box<Node>, body: Box<Node>, else_body: Option<Box<Node>> }, While { cond: Box<Node>, body: Box<Node> },
     For { param: Box<Node>, iter: Box<Node>, body: Box<Node> },
     ForRange { param: Box<Node>, start: Box<Node>, end: Box<Node>, step: Option<Box<Node>>, body: Box<Node> },
     IfExpr { cond: Box<Node>, node: Box<Node>, else_node: Box<Node> },
-    Proc { name: Box<Node>, params: ProcParams, body: Box<Node> }, Rule { name: Box<Node>, id: Box<Node>, rules: Rules },
+    Proc { name: Box<Node>, params: ProcParams, body: Box<Node> }, Rule { name: Box<Node>, params: Vec<Node>, rules: Rules },
+    Shape { name: Box<Node>, fields: Vec<(Node, Node)> },
     Container { name: Box<Node>, body: Box<Node> },
     Field { head: Box<Node>, field: Box<Node> }, FieldExpr { head: Box<Node>, expr: Box<Node> },
 }
@@ -32,14 +35,19 @@ impl N {
             Self::Float(_) => "float",
             Self::Bool(_) => "bool",
             Self::String(_) => "str",
+            Self::Template(_) => "template string",
+            Self::Bytes(_) => "bytes",
             Self::Vector(_) => "vec",
             Self::Tuple(_) => "tuple",
             Self::Object(_) => "obj",
+            Self::Set(_) => "set",
+            Self::Map(_) => "map",
             Self::ID(_) => "identifier",
             Self::Type(_) => "type",
             Self::Binary { op:_, left:_, right:_ } => "binary operation",
             Self::Unary { op:_, node:_ } => "unary operation",
             Self::Multi { op:_, nodes:_ } => "multi operation",
+            Self::Range { start:_, end:_, step:_, inclusive:_ } => "range",
             Self::Assign { global:_, id:_, expr:_ } => "assignment",
             Self::OpAssign { op:_, id:_, expr:_ } => "operator assignment",
             Self::Inc(_) => "incrementation",
@@ -47,6 +55,7 @@ impl N {
             Self::Return(_) => "return",
             Self::Break => "break",
             Self::Continue => "continue",
+            Self::Del(_) => "deletion",
             Self::Call { id:_, args:_ } => "call",
             Self::CallExpr { id:_, args:_ } => "call expression",
             Self::If { cond:_, body:_, else_body:_ } => "if statement",
@@ -55,7 +64,8 @@ impl N {
             Self::ForRange { param:_, start:_, end:_, step:_, body:_ } => "for-range statement",
             Self::IfExpr { cond:_, node:_, else_node:_ } => "if expression",
             Self::Proc { name:_, params:_, body:_ } => "procedure definition",
-            Self::Rule { name:_, id:_, rules:_ } => "rule definition",
+            Self::Rule { name:_, params:_, rules:_ } => "rule definition",
+            Self::Shape { name:_, fields:_ } => "shape definition",
             Self::Container { name:_, body:_ } => "container definition",
             Self::Field { head:_, field:_ } => "field",
             Self::FieldExpr { head:_, expr:_ } => "field expression",
@@ -73,18 +83,29 @@ impl std::fmt::Display for N {
             Self::Float(v) => write!(f, "{v:?}"),
             Self::Bool(v) => write!(f, "{v:?}"),
             Self::String(v) => write!(f, "{v:?}"),
+            Self::Bytes(v) => write!(f, "{}", bytes_repr(v)),
+            Self::Template(nodes) => write!(f, "\"{}\"", nodes.iter().map(|x| match &x.0 {
+                N::String(s) => s.clone(),
+                _ => format!("{{{x}}}")
+            }).collect::<String>()),
             Self::Vector(v) => write!(f, "[{}]", v.iter().map(|x| x.to_string())
             .collect::<Vec<String>>().join(", ")),
             Self::Tuple(v) => write!(f, "({})", v.iter().map(|x| x.to_string())
             .collect::<Vec<String>>().join(", ")),
             Self::Object(v) => write!(f, "{{ {} }}", v.iter().map(|(k, v)| format!("{k} = {v}"))
             .collect::<Vec<String>>().join(", ")),
+            Self::Set(v) => write!(f, "{{ {} }}", v.iter().map(|x| x.to_string())
+            .collect::<Vec<String>>().join(", ")),
+            Self::Map(v) => write!(f, "{{ {} }}", v.iter().map(|(k, v)| format!("{k}: {v}"))
+            .collect::<Vec<String>>().join(", ")),
             Self::ID(v) => write!(f, "{v}"),
             Self::Type(v) => write!(f, "{v}"),
             Self::Binary { op, left, right } => write!(f, "{left} {op} {right}"),
             Self::Unary { op, node } => write!(f, "{op} {node}"),
             Self::Multi { op, nodes } => write!(f, "{op} {}", nodes.iter().map(|x| x.to_string())
             .collect::<Vec<String>>().join(" ")),
+            Self::Range { start, end, step, inclusive } => write!(f, "{start}{}{end}{}",
+                if *inclusive { "..=" } else { ".." }, step.as_ref().map(|x| format!("..{x}")).unwrap_or_default()),
             Self::Assign { global, id, expr } => if *global {
                 write!(f, "global {id} = {expr}")
             } else {
@@ -96,6 +117,7 @@ impl std::fmt::Display for N {
             Self::Return(node) => write!(f, "return {node}"),
             Self::Break => write!(f, "break"),
             Self::Continue => write!(f, "continue"),
+            Self::Del(node) => write!(f, "del {node}"),
             Self::Dec(id) => write!(f, "{id}--"),
             Self::Call { id, args } => write!(f, "{id}! {}", args.iter().map(|x| x.to_string())
             .collect::<Vec<String>>().join(", ")),
@@ -117,12 +139,15 @@ impl std::fmt::Display for N {
                     None => format!("{id}")
                 }
             ).collect::<Vec<String>>().join(", ")),
-            Self::Rule { name, id, rules } => write!(f, "rule {name} <- {id}; {}",
-            rules.iter().map(|(rule, new)| match new {
+            Self::Rule { name, params, rules } => write!(f, "rule {name} <- {}; {}",
+            params.iter().map(|x| x.to_string()).collect::<Vec<String>>().join(", "),
+            rules.iter().map(|(rule, new, _)| match new {
                 Some(new) => format!("{rule} : {new}"),
                 None => format!("{rule}")
             })
             .collect::<Vec<String>>().join("; ")),
+            Self::Shape { name, fields } => write!(f, "shape {name} {{ {} }}",
+            fields.iter().map(|(k, v)| format!("{k} : {v}")).collect::<Vec<String>>().join(", ")),
             Self::Container { name, body } => write!(f, "proc {name} {body}"),
             Self::Field { head, field } => write!(f, "{head}.{field}"),
             Self::FieldExpr { head, expr } => write!(f, "{head}[{expr}]"),
@@ -148,17 +173,29 @@ impl Node {
             N::Float(v) => format!("{v:?}"),
             N::Bool(v) => format!("{v:?}"),
             N::String(v) => format!("{v:?}"),
+            N::Bytes(v) => bytes_repr(v),
+            N::Template(nodes) => format!("\"{}\"", nodes.iter().map(|x| match &x.0 {
+                N::String(s) => s.clone(),
+                _ => format!("{{{}}}", x.display(indent))
+            }).collect::<String>()),
             N::Vector(v) => format!("[{}]", v.iter().map(|x| x.display(indent)).collect::<Vec<String>>().join(", ")),
             N::Tuple(v) => format!("({})", v.iter().map(|x| x.display(indent)).collect::<Vec<String>>().join(", ")),
             N::Object(v) => format!("{{ {} }}",
             v.iter().map(|(k, v)| format!("{} = {}", k.display(indent), v.display(indent)))
                 .collect::<Vec<String>>().join(", ")),
+            N::Set(v) => format!("{{ {} }}", v.iter().map(|x| x.display(indent)).collect::<Vec<String>>().join(", ")),
+            N::Map(v) => format!("{{ {} }}",
+            v.iter().map(|(k, v)| format!("{}: {}", k.display(indent), v.display(indent)))
+                .collect::<Vec<String>>().join(", ")),
             N::ID(v) => format!("{v}"),
             N::Type(v) => format!("{v}"),
             N::Binary { op, left, right } => format!("{} {op} {}", left.display(indent), right.display(indent)),
             N::Unary { op, node } => format!("{op} {}", node.display(indent)),
             N::Multi { op, nodes } => format!("{op} {}",
                 nodes.iter().map(|x| x.display(indent)).collect::<Vec<String>>().join(" ")),
+            N::Range { start, end, step, inclusive } => format!("{}{}{}{}", start.display(indent),
+                if *inclusive { "..=" } else { ".." }, end.display(indent),
+                step.as_ref().map(|x| format!("..{}", x.display(indent))).unwrap_or_default()),
             N::Assign { global, id, expr } => if *global {
                 format!("{s}global {} = {}", id.display(indent), expr.display(indent))
             } else {
@@ -170,6 +207,7 @@ impl Node {
             N::Return(node) => format!("{s}return {}", node.display(indent)),
             N::Break => format!("{s}break"),
             N::Continue => format!("{s}continue"),
+            N::Del(node) => format!("{s}del {}", node.display(indent)),
             N::Call { id, args } => format!("{s}{}! {}", id.display(indent),
                 args.iter().map(|x| x.display(indent)).collect::<Vec<String>>().join(", ")),
             N::CallExpr { id, args } => format!("{}({})", id.display(indent),
@@ -193,13 +231,16 @@ impl Node {
                         None => format!("{}", id.display(indent))
                     }
                 ).collect::<Vec<String>>().join(" "), body.display(indent + 1)),
-            N::Rule { name, id, rules } => format!("{s}rule {} <- {}\n{}",
-                name.display(indent), id.display(indent),
-                rules.iter().map(|(rule, new)| match new {
+            N::Rule { name, params, rules } => format!("{s}rule {} <- {}\n{}",
+                name.display(indent), params.iter().map(|x| x.display(indent)).collect::<Vec<String>>().join(", "),
+                rules.iter().map(|(rule, new, _)| match new {
                     Some(new) => format!("{s}    {} : {}", rule.display(indent), new.display(indent)),
                     None => format!("{s}    {}", rule.display(indent))
                 })
                 .collect::<Vec<String>>().join("\n")),
+            N::Shape { name, fields } => format!("{s}shape {} {{ {} }}", name.display(indent),
+                fields.iter().map(|(k, v)| format!("{} : {}", k.display(indent), v.display(indent)))
+                .collect::<Vec<String>>().join(", ")),
             N::Container { name, body } => format!("{s}proc {}\n{}", name.display(indent), body.display(indent + 1)),
             N::Field { head, field } => format!("{}.{}", head.display(indent), field.display(indent)),
             N::FieldExpr { head, expr } => format!("{}[{}]", head.display(indent), expr.display(indent)),
@@ -209,31 +250,39 @@ impl Node {
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Layer {
-    Binary(Vec<T>), UnaryLeft(Vec<T>), UnaryRight(Vec<T>),
+    Binary(Vec<T>), BinaryRight(Vec<T>), UnaryLeft(Vec<T>), UnaryRight(Vec<T>), Range,
     Atom
 }
 
 pub struct Parser {
     tokens: Vec<Vec<Token>>,
+    lines: Vec<String>,
     path: String,
     col: usize,
     ln: usize,
     layers: Vec<Layer>,
 }
 impl Parser {
-    pub fn new(path: &String, tokens: Vec<Vec<Token>>) -> Self {
+    pub fn new(path: &String, text: &String, tokens: Vec<Vec<Token>>) -> Self {
         Self {
-            tokens, path: path.clone(), col: 0, ln: 0,
+            tokens, lines: text.split("\n").map(|x| x.to_string()).collect(), path: path.clone(), col: 0, ln: 0,
             layers: vec![
                 Layer::Binary(vec![T::And, T::Or, T::Xor]),
                 Layer::UnaryLeft(vec![T::Not]),
                 Layer::Binary(vec![T::EQ, T::NE, T::LT, T::LE, T::GT, T::GE, T::Is, T::Contains]),
+                // `start..end`, `start..=end` and `start..end..step`
+                Layer::Range,
+                // `|` is the bitwise or of ints, the union of sets or between types a union type,
+                // `binary` tells them apart, comparisons stay looser so `x is int | str` checks for the union
+                Layer::Binary(vec![T::Option]),
+                Layer::Binary(vec![T::BitAnd]),
+                Layer::Binary(vec![T::Shl, T::Shr]),
                 Layer::Binary(vec![T::Add, T::Sub]),
                 Layer::Binary(vec![T::Mul, T::Div, T::Mod]),
-                Layer::UnaryLeft(vec![T::Sub]),
+                Layer::UnaryLeft(vec![T::Sub, T::BitNot]),
+                Layer::BinaryRight(vec![T::Pow]),
                 Layer::UnaryLeft(vec![T::Len]),
                 Layer::UnaryRight(vec![T::Nullable]),
-                Layer::Binary(vec![T::Option]),
             ],
         }
     }
@@ -270,6 +319,17 @@ impl Parser {
             None => &(0..1)
         }
     }
+    /// the source line of the current token line, a multi-line string makes the lines after it
+    /// come later in the source than their index in `tokens`
+    pub fn line(&self) -> usize {
+        match self.tokens.get(self.ln).and_then(|line| line.first()) {
+            Some(token) => (token.1).0.start,
+            // an empty line counts on from the last line with tokens
+            None => self.tokens[..self.ln.min(self.tokens.len())].iter().enumerate().rev()
+                .find_map(|(ln, line)| line.last().map(|token| (token.1).0.start + self.ln - ln))
+                .unwrap_or(self.ln)
+        }
+    }
     pub fn advance(&mut self) { self.col += 1; }
     pub fn revert(&mut self) { self.col -= if self.col > 0 { 1 } else { 0 }; }
     pub fn advance_ln(&mut self) { self.ln += 1; self.col = 0; }
@@ -311,7 +371,16 @@ impl Parser {
                 while ops.contains(&self.token()) {
                     let op = self.token().to_owned();
                     self.advance();
-                    let right = self.operation(self.ops(layer + 1), layer + 1, context)?;
+                    // `x is not rule`
+                    let right = if op == T::Is && self.token() == &T::Not {
+                        let start = self.col().start;
+                        self.advance();
+                        let node = self.operation(self.ops(layer + 1), layer + 1, context)?;
+                        let stop = (node.1).1.end;
+                        Node(N::Unary { op: T::Not, node: Box::new(node) }, Position::new(self.line()..self.line()+1, start..stop))
+                    } else {
+                        self.operation(self.ops(layer + 1), layer + 1, context)?
+                    };
                     let stop = (right.1).1.end;
                     let n = match &left.0 {
                         N::Binary { op: op_, left: left_, right: right_ } if &op == op_ => 
@@ -326,10 +395,23 @@ impl Parser {
                             op, left: Box::new(left.clone()), right: Box::new(right)
                         }
                     };
-                    left = Node(n, Position::new(self.ln..self.ln+1, start..stop))
+                    left = Node(n, Position::new(self.line()..self.line()+1, start..stop))
                 }
                 Ok(left)
             }
+            // `a ^ b ^ c` is `a ^ (b ^ c)`, the right side goes through the layer above so `2 ^ -1` parses
+            Layer::BinaryRight(ops) => {
+                let start = self.col().start;
+                let left = self.operation(self.ops(layer + 1), layer + 1, context)?;
+                if !ops.contains(&self.token()) { return Ok(left) }
+                let op = self.token().to_owned();
+                self.advance();
+                let right = self.operation(self.ops(layer - 1), layer - 1, context)?;
+                let stop = (right.1).1.end;
+                Ok(Node(N::Binary {
+                    op, left: Box::new(left), right: Box::new(right)
+                }, Position::new(self.line()..self.line()+1, start..stop)))
+            }
             Layer::UnaryLeft(ops) => {
                 let start = self.col().start;
                 if ops.contains(&self.token()) {
@@ -339,7 +421,7 @@ impl Parser {
                     let stop = (node.1).1.end;
                     return Ok(Node(N::Unary{
                         op, node: Box::new(node)
-                    }, Position::new(self.ln..self.ln+1, start..stop)))
+                    }, Position::new(self.line()..self.line()+1, start..stop)))
                 }
                 self.operation(self.ops(layer + 1), layer + 1, context)
             }
@@ -352,10 +434,31 @@ impl Parser {
                     self.advance();
                     node = Node(N::Unary{
                         op, node: Box::new(node.clone())
-                    }, Position::new(self.ln..self.ln+1, start..stop));
+                    }, Position::new(self.line()..self.line()+1, start..stop));
                 }
                 Ok(node)
             }
+            Layer::Range => {
+                let start = self.col().start;
+                let first = self.operation(self.ops(layer + 1), layer + 1, context)?;
+                let inclusive = match self.token() {
+                    T::Range => false,
+                    T::RangeInclusive => true,
+                    _ => return Ok(first)
+                };
+                self.advance();
+                let end = self.operation(self.ops(layer + 1), layer + 1, context)?;
+                let step = if self.token() == &T::Range {
+                    self.advance();
+                    Some(Box::new(self.operation(self.ops(layer + 1), layer + 1, context)?))
+                } else {
+                    None
+                };
+                let stop = step.as_ref().map(|x| &x.1).unwrap_or(&end.1).1.end;
+                Ok(Node(N::Range {
+                    start: Box::new(first), end: Box::new(end), step, inclusive
+                }, Position::new(self.line()..self.line()+1, start..stop)))
+            }
             Layer::Atom => self.call(context)
         }
     }
@@ -368,7 +471,7 @@ impl Parser {
             let node = self.stat(indent, context)?;
             nodes.push(node);
         }
-        Ok(Node(N::Body(nodes), Position::new(0..self.ln, 0..self.col().end)))
+        Ok(Node(N::Body(nodes), Position::new(0..self.line(), 0..self.col().end)))
     }
     pub fn stat(&mut self, start_indent: u16, context: &mut Context) -> Result<Node, E> {
         let start = self.col().start;
@@ -376,7 +479,7 @@ impl Parser {
         if let T::Indent(i) = self.token() { indent += *i; self.advance(); }
         match self.token() {
             T::Var | T::Global => {
-                let (start_ln, start_col) = (self.ln, self.col().start);
+                let (start_ln, start_col) = (self.line(), self.col().start);
                 let prefix = self.token().to_owned();
                 self.advance();
                 let id = self.field(context)?;
@@ -390,13 +493,13 @@ impl Parser {
                 }, Position::new(start_ln..stop_ln, start_col..stop_col)))
             }
             T::If => {
-                let (start_ln, mut stop_ln, start_col, mut stop_col) = (self.ln, self.col().start, self.ln, self.col().end);
+                let (start_ln, mut stop_ln, start_col, mut stop_col) = (self.line(), self.col().start, self.line(), self.col().end);
                 self.advance();
                 let cond = self.expr(context)?;
                 self.expect(T::EOL, context)?;
                 self.advance_ln();
                 let mut nodes: Vec<Node> = vec![];
-                let (body_start_ln, body_start_col) = (self.ln, self.col().start);
+                let (body_start_ln, body_start_col) = (self.line(), self.col().start);
                 while let T::Indent(i) = self.token() {
                     if *i <= indent { break }
                     let node = self.stat(0, context)?;
@@ -414,7 +517,7 @@ impl Parser {
                     } else {
                         self.advance_ln();
                         let mut else_nodes: Vec<Node> = vec![];
-                        let (else_start_ln, else_start_col) = (self.ln, self.col().start);
+                        let (else_start_ln, else_start_col) = (self.line(), self.col().start);
                         while let T::Indent(i) = self.token() {
                             if *i <= indent { break }
                             let node = self.stat(0, context)?;
@@ -430,13 +533,13 @@ impl Parser {
                 }, Position::new(start_ln..stop_ln, start_col..stop_col)))
             }
             T::While => {
-                let (start_ln, mut stop_ln, start_col, mut stop_col) = (self.ln, self.col().start, self.ln, self.col().end);
+                let (start_ln, mut stop_ln, start_col, mut stop_col) = (self.line(), self.col().start, self.line(), self.col().end);
                 self.advance();
                 let cond = self.expr(context)?;
                 self.expect(T::EOL, context)?;
                 self.advance_ln();
                 let mut nodes: Vec<Node> = vec![];
-                let (body_start_ln, body_start_col) = (self.ln, self.col().start);
+                let (body_start_ln, body_start_col) = (self.line(), self.col().start);
                 while let T::Indent(i) = self.token() {
                     if *i <= indent { break }
                     let node = self.stat(0, context)?;
@@ -449,7 +552,7 @@ impl Parser {
                 }, Position::new(start_ln..stop_ln, start_col..stop_col)))
             }
             T::For => {
-                let (start_ln, mut stop_ln, start_col, mut stop_col) = (self.ln, self.col().start, self.ln, self.col().end);
+                let (start_ln, mut stop_ln, start_col, mut stop_col) = (self.line(), self.col().start, self.line(), self.col().end);
                 self.advance();
                 let param = self.atom(context)?;
                 if self.token() == &T::Out {
@@ -458,7 +561,7 @@ impl Parser {
                     self.expect(T::EOL, context)?;
                     self.advance_ln();
                     let mut nodes: Vec<Node> = vec![];
-                    let (body_start_ln, body_start_col) = (self.ln, self.col().start);
+                    let (body_start_ln, body_start_col) = (self.line(), self.col().start);
                     while let T::Indent(i) = self.token() {
                         if *i <= indent { break }
                         let node = self.stat(0, context)?;
@@ -482,7 +585,7 @@ impl Parser {
                     self.expect(T::EOL, context)?;
                     self.advance_ln();
                     let mut nodes: Vec<Node> = vec![];
-                    let (body_start_ln, body_start_col) = (self.ln, self.col().start);
+                    let (body_start_ln, body_start_col) = (self.line(), self.col().start);
                     while let T::Indent(i) = self.token() {
                         if *i <= indent { break }
                         let node = self.stat(0, context)?;
@@ -499,7 +602,7 @@ impl Parser {
                 }
             }
             T::Return => {
-                let (start_ln, start_col) = (self.ln, self.col().start);
+                let (start_ln, start_col) = (self.line(), self.col().start);
                 self.advance();
                 let node = self.expr(context)?;
                 let (stop_ln, stop_col) = ((node.1).0.end, (node.1).1.end);
@@ -521,8 +624,17 @@ impl Parser {
                 self.advance_ln();
                 Ok(node)
             }
+            T::Del => {
+                let (start_ln, start_col) = (self.line(), self.col().start);
+                self.advance();
+                let node = self.field(context)?;
+                let (stop_ln, stop_col) = ((node.1).0.end, (node.1).1.end);
+                self.expect(T::EOL, context)?;
+                self.advance_ln();
+                Ok(Node(N::Del(Box::new(node)), Position::new(start_ln..stop_ln, start_col..stop_col)))
+            }
             T::Proc => {
-                let (start_ln, mut stop_ln, start_col, mut stop_col) = (self.ln, self.col().start, self.ln, self.col().end);
+                let (start_ln, mut stop_ln, start_col, mut stop_col) = (self.line(), self.col().start, self.line(), self.col().end);
                 self.advance();
                 let name = self.atom(context)?;
                 let mut params: ProcParams = vec![];
@@ -547,7 +659,7 @@ impl Parser {
                 self.expect(T::EOL, context)?;
                 self.advance_ln();
                 let mut nodes: Vec<Node> = vec![];
-                let (body_start_ln, body_start_col) = (self.ln, self.col().start);
+                let (body_start_ln, body_start_col) = (self.line(), self.col().start);
                 while let T::Indent(i) = self.token() {
                     if *i <= indent { break }
                     let node = self.stat(0, context)?;
@@ -560,13 +672,13 @@ impl Parser {
                 }, Position::new(start_ln..stop_ln, start_col..stop_col)))
             }
             T::Container => {
-                let (start_ln, mut stop_ln, start_col, mut stop_col) = (self.ln, self.col().start, self.ln, self.col().end);
+                let (start_ln, mut stop_ln, start_col, mut stop_col) = (self.line(), self.col().start, self.line(), self.col().end);
                 self.advance();
                 let name = self.atom(context)?;
                 self.expect(T::EOL, context)?;
                 self.advance_ln();
                 let mut nodes: Vec<Node> = vec![];
-                let (body_start_ln, body_start_col) = (self.ln, self.col().start);
+                let (body_start_ln, body_start_col) = (self.line(), self.col().start);
                 while let T::Indent(i) = self.token() {
                     if *i <= indent { break }
                     let node = self.stat(0, context)?;
@@ -579,17 +691,24 @@ impl Parser {
                 }, Position::new(start_ln..stop_ln, start_col..stop_col)))
             }
             T::Rule => {
-                let (start_ln, mut stop_ln, start_col, mut stop_col) = (self.ln, self.col().start, self.ln, self.col().end);
+                let (start_ln, mut stop_ln, start_col, mut stop_col) = (self.line(), self.col().start, self.line(), self.col().end);
                 self.advance();
                 let name = self.atom(context)?;
                 self.advance_expect(T::In, context)?;
-                let id = self.atom(context)?;
-                self.expect(T::EOL, context)?;
+                let mut params: Vec<Node> = vec![];
+                while self.token() != &T::EOL {
+                    params.push(self.atom(context)?);
+                    self.advance_if(T::Sep);
+                }
                 self.advance_ln();
                 let mut rules: Rules = vec![];
                 while let T::Indent(i) = self.token() {
                     if *i <= indent { break }
                     self.advance();
+                    let source = match self.lines.get(self.pos().0.start) {
+                        Some(line) => line.trim().to_string(),
+                        None => String::new()
+                    };
                     let node = self.expr(context)?;
                     (stop_ln, stop_col) = ((node.1).0.end, (node.1).1.end);
                     let mut new: Option<Node> = None;
@@ -598,13 +717,36 @@ impl Parser {
                         new = Some(self.expr(context)?);
                     }
                     self.expect(T::EOL, context)?;
-                    rules.push((node, new));
+                    rules.push((node, new, source));
                     self.advance_ln();
                 }
                 Ok(Node(N::Rule {
-                    name: Box::new(name), id: Box::new(id), rules
+                    name: Box::new(name), params, rules
                 }, Position::new(start_ln..stop_ln, start_col..stop_col)))
             }
+            T::Shape => {
+                let (start_ln, start_col) = (self.line(), self.col().start);
+                self.advance();
+                let name = self.atom(context)?;
+                self.expect(T::ObjectIn, context)?;
+                self.advance_line_break();
+                let mut fields: Vec<(Node, Node)> = vec![];
+                while self.token() != &T::ObjectOut {
+                    let key = self.atom(context)?;
+                    self.advance_expect(T::Rep, context)?;
+                    let constraint = self.expr(context)?;
+                    self.advance_if(T::Sep);
+                    self.advance_if_line_break();
+                    fields.push((key, constraint));
+                }
+                let (stop_ln, stop_col) = (self.line(), self.col().end);
+                self.advance();
+                self.expect(T::EOL, context)?;
+                self.advance_ln();
+                Ok(Node(N::Shape {
+                    name: Box::new(name), fields
+                }, Position::new(start_ln..stop_ln+1, start_col..stop_col)))
+            }
             _ => {
                 let node = self.field(context)?;
                 if [T::Assign, T::AddAssign, T::SubAssign, T::MulAssign, T::DivAssign, T::ModAssign].contains(&self.token()) {
@@ -619,14 +761,14 @@ impl Parser {
                     }, Position::new(ln..ln, start..col)))
                 }
                 if self.token() == &T::Inc {
-                    let (ln, col) = (self.ln, self.col().start);
+                    let (ln, col) = (self.line(), self.col().start);
                     self.advance();
                     self.expect(T::EOL, context)?;
                     self.advance_ln();
                     return Ok(Node(N::Inc(Box::new(node)), Position::new(ln..ln+1, start..col+1)))
                 }
                 if self.token() == &T::Dec {
-                    let (ln, col) = (self.ln, self.col().start);
+                    let (ln, col) = (self.line(), self.col().start);
                     self.advance();
                     self.expect(T::EOL, context)?;
                     self.advance_ln();
@@ -639,7 +781,7 @@ impl Parser {
                     self.advance_if(T::Sep);
                     args.push(value);
                 }
-                let (ln, col) = (self.ln, self.col().start);
+                let (ln, col) = (self.line(), self.col().start);
                 self.advance_ln();
                 Ok(Node(N::Call {
                     id: Box::new(node), args
@@ -658,12 +800,12 @@ impl Parser {
             let stop = (else_node.1).1.end;
             return Ok(Node(N::IfExpr {
                 cond: Box::new(cond), node: Box::new(node), else_node: Box::new(else_node)
-            }, Position::new(self.ln..self.ln+1, start..stop)))
+            }, Position::new(self.line()..self.line()+1, start..stop)))
         }
         Ok(node)
     }
     pub fn call(&mut self, context: &mut Context) -> Result<Node, E> {
-        let (start_ln, start_col) = (self.ln, self.col().start);
+        let (start_ln, start_col) = (self.line(), self.col().start);
         let node = self.field(context)?;
         if self.token() == &T::EvalIn {
             self.advance_line_break();
@@ -674,7 +816,7 @@ impl Parser {
                 self.advance_if_line_break();
                 args.push(arg);
             }
-            let (stop_ln, stop_col) = (self.ln, self.col().end);
+            let (stop_ln, stop_col) = (self.line(), self.col().end);
             self.advance();
             return Ok(Node(N::CallExpr {
                 id: Box::new(node), args
@@ -683,13 +825,20 @@ impl Parser {
         Ok(node)
     }
     pub fn field(&mut self, context: &mut Context) -> Result<Node, E> {
-        let (start_ln, start_col) = (self.ln, self.col().start);
+        let (start_ln, start_col) = (self.line(), self.col().start);
         let mut head = self.atom(context)?;
         while self.token() == &T::Field || self.token() == &T::VectorIn {
             match self.token() {
                 T::Field => {
                     self.advance();
-                    let field = self.atom(context)?;
+                    // type keywords name fields too, like `random.int`
+                    let field = if let T::Type(typ) = self.token().clone() {
+                        let pos = self.pos().clone();
+                        self.advance();
+                        Node(N::ID(typ.name().to_string()), pos)
+                    } else {
+                        self.atom(context)?
+                    };
                     let (stop_ln, stop_col) = ((field.1).0.end, (field.1).1.end);
                     head = Node(N::Field {
                         head: Box::new(head.clone()), field: Box::new(field) }
@@ -697,8 +846,18 @@ impl Parser {
                 }
                 T::VectorIn => {
                     self.advance();
-                    let expr = self.expr(context)?;
-                    let (stop_ln, stop_col) = (self.ln, self.col);
+                    let mut expr = self.expr(context)?;
+                    // `map[str, int]` indexes with a tuple
+                    if self.token() == &T::Sep {
+                        let start = expr.1.clone();
+                        let mut nodes = vec![expr];
+                        while self.token() == &T::Sep {
+                            self.advance();
+                            nodes.push(self.expr(context)?);
+                        }
+                        expr = Node(N::Tuple(nodes), Position::new(start.0.start..self.line()+1, start.1.start..self.col().start));
+                    }
+                    let (stop_ln, stop_col) = (self.line(), self.col);
                     self.advance_expect(T::VectorOut, context)?;
                     head = Node(N::FieldExpr {
                         head: Box::new(head.clone()), expr: Box::new(expr) }
@@ -717,6 +876,29 @@ impl Parser {
             T::Float(v) => Ok(Node(N::Float(*v), self.pos().to_owned())),
             T::Bool(v) => Ok(Node(N::Bool(*v), self.pos().to_owned())),
             T::String(v) => Ok(Node(N::String(v.to_owned()), self.pos().to_owned())),
+            T::Bytes(v) => Ok(Node(N::Bytes(v.to_owned()), self.pos().to_owned())),
+            T::Template(parts) => {
+                let mut nodes: Vec<Node> = vec![];
+                for part in parts.clone() {
+                    match part {
+                        TemplatePart::Str(s) => nodes.push(Node(N::String(s), self.pos().to_owned())),
+                        TemplatePart::Expr(mut tokens) => {
+                            // the embedded expression is parsed on its own, its tokens keep their place in the file
+                            let end = match tokens.last() {
+                                Some(token) => token.1.clone(),
+                                None => self.pos().to_owned()
+                            };
+                            tokens.push(Token(T::EOL, Position::new(end.0.clone(), end.1.end..end.1.end)));
+                            let mut parser = Parser::new(&self.path, &String::new(), vec![tokens, vec![Token(T::EOF, end)]]);
+                            parser.lines = self.lines.clone();
+                            let node = parser.expr(context)?;
+                            parser.expect(T::EOL, context)?;
+                            nodes.push(node);
+                        }
+                    }
+                }
+                Ok(Node(N::Template(nodes), self.pos().to_owned()))
+            }
             T::Type(v) => Ok(Node(N::Type(v.to_owned()), self.pos().to_owned())),
             T::ID(id) => Ok(Node(N::ID(id.to_owned()), self.pos().to_owned())),
             T::EvalIn => {
@@ -730,13 +912,13 @@ impl Parser {
                         let node = self.expr(context)?;
                         nodes.push(node);
                     }
-                    Ok(Node(N::Tuple(nodes), Position::new(self.ln..self.ln+1, start..self.col().end)))
+                    Ok(Node(N::Tuple(nodes), Position::new(self.line()..self.line()+1, start..self.col().end)))
                 } else {
                     Ok(node)
                 }
             }
             T::VectorIn => {
-                let (start_ln, start_col) = (self.ln, self.col().start);
+                let (start_ln, start_col) = (self.line(), self.col().start);
                 self.advance_line_break();
                 let mut nodes: Vec<Node> = vec![];
                 while self.token() != &T::VectorOut {
@@ -746,22 +928,43 @@ impl Parser {
                     nodes.push(node);
                 }
                 // some weird shit is going on here with the operation function
-                Ok(Node(N::Vector(nodes), Position::new(start_ln..self.ln+1, start_col..self.col().end)))
+                Ok(Node(N::Vector(nodes), Position::new(start_ln..self.line()+1, start_col..self.col().end)))
             }
             T::ObjectIn => {
-                let (start_ln, start_col) = (self.ln, self.col().start);
+                let (start_ln, start_col) = (self.line(), self.col().start);
                 self.advance_line_break();
                 let mut nodes: Vec<(Node, Node)> = vec![];
+                let mut elements: Vec<Node> = vec![];
+                // the first entry tells an obj `{ a = 1 }` from a map `{ "a": 1 }` and a set `{ 1, 2 }`
+                let mut kind = T::Assign;
                 while self.token() != &T::ObjectOut {
-                    let key = self.atom(context)?;
-                    self.advance_expect(T::Assign, context)?;
-                    let expr = self.expr(context)?;
+                    let bare = matches!(self.token(), T::ID(_));
+                    let key = self.expr(context)?;
+                    if nodes.is_empty() && elements.is_empty() {
+                        kind = if [T::Assign, T::Rep].contains(self.token()) { self.token().clone() } else { T::Sep };
+                    }
+                    if kind == T::Sep {
+                        elements.push(key);
+                    } else {
+                        self.advance_expect(kind.clone(), context)?;
+                        // a bare name is the key of an obj itself, `(key)` is evaluated
+                        let key = match key {
+                            Node(N::ID(id), pos) if bare && kind == T::Assign => Node(N::String(id), pos),
+                            key => key
+                        };
+                        let expr = self.expr(context)?;
+                        nodes.push((key, expr));
+                    }
                     self.advance_if(T::Sep);
                     self.advance_if_line_break();
-                    nodes.push((key, expr));
                 }
+                let node = match kind {
+                    T::Sep => N::Set(elements),
+                    T::Rep => N::Map(nodes),
+                    _ => N::Object(nodes)
+                };
                 // some weird shit is going on here with the operation function
-                Ok(Node(N::Object(nodes), Position::new(start_ln..self.ln+1, start_col..self.col().end)))
+                Ok(Node(node, Position::new(start_ln..self.line()+1, start_col..self.col().end)))
             }
             _ => {
                 context.trace(self.pos().to_owned());
@@ -773,6 +976,6 @@ impl Parser {
     }
 }
 
-pub fn parse(path: &String, tokens: Vec<Vec<Token>>, context: &mut Context) -> Result<Node, E> {
-    Parser::new(path, tokens).parse(context)
+pub fn parse(path: &String, text: &String, tokens: Vec<Vec<Token>>, context: &mut Context) -> Result<Node, E> {
+    Parser::new(path, text, tokens).parse(context)
 }
\ No newline at end of file
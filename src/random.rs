@@ -0,0 +1,78 @@
+use std::cell::Cell;
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::*;
+
+thread_local! {
+    /// the state of the generator, seeded from the clock until `random.seed` is called
+    static STATE: Cell<u64> = Cell::new(
+        SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos() as u64).unwrap_or(0)
+    );
+}
+/// splitmix64, small and good enough for scripts, not for cryptography
+fn next_u64() -> u64 {
+    STATE.with(|state| {
+        let s = state.get().wrapping_add(0x9e3779b97f4a7c15);
+        state.set(s);
+        let mut z = s;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    })
+}
+/// a uniform number below `n` (or any number if `n` is `0`), rejecting the biased top values
+fn below(n: u64) -> u64 {
+    if n == 0 { return next_u64() }
+    let zone = u64::MAX - u64::MAX % n;
+    loop {
+        let v = next_u64();
+        if v < zone { return v % n }
+    }
+}
+
+pub fn random_seed(context: &mut Context, pos: &Position) -> Result<V, E> {
+    if let Some(V::Int(n)) = context.get(&String::from("n")) {
+        STATE.with(|state| state.set(*n as u64));
+    }
+    Ok(V::Null)
+}
+/// an `int` from `lo` to `hi`, both included
+pub fn random_int(context: &mut Context, pos: &Position) -> Result<V, E> {
+    let (lo, hi) = match (context.get(&String::from("lo")), context.get(&String::from("hi"))) {
+        (Some(V::Int(lo)), Some(V::Int(hi))) => (*lo, *hi),
+        _ => (0, 0)
+    };
+    if lo > hi {
+        context.trace(pos.clone());
+        return Err(E::EmptyRange(lo, hi))
+    }
+    let span = (hi as u64).wrapping_sub(lo as u64).wrapping_add(1);
+    Ok(V::Int(lo.wrapping_add(below(span) as i64)))
+}
+/// a `float` from `0.0` up to but not including `1.0`
+pub fn random_float(context: &mut Context, pos: &Position) -> Result<V, E> {
+    Ok(V::Float((next_u64() >> 11) as f64 / (1u64 << 53) as f64))
+}
+pub fn random_choice(context: &mut Context, pos: &Position) -> Result<V, E> {
+    let values = match context.get(&String::from("values")) {
+        Some(V::Vector(values, _)) => values.clone(),
+        _ => vec![]
+    };
+    if values.is_empty() {
+        context.trace(pos.clone());
+        return Err(E::IndexRange(0, 0))
+    }
+    Ok(values[below(values.len() as u64) as usize].clone())
+}
+/// a shuffled copy of the vector
+pub fn random_shuffle(context: &mut Context, pos: &Position) -> Result<V, E> {
+    match context.get(&String::from("values")) {
+        Some(V::Vector(values, types)) => {
+            let mut values = values.clone();
+            for i in (1..values.len()).rev() {
+                values.swap(i, below(i as u64 + 1) as usize);
+            }
+            Ok(V::Vector(values, types.clone()))
+        }
+        _ => Ok(V::Null)
+    }
+}
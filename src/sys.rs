@@ -0,0 +1,72 @@
+use std::process::Command;
+use crate::*;
+
+/// sets `sys.args` to the arguments given after the script path
+pub fn set_args(context: &mut Context, args: &[String]) {
+    if let Some(V::Container(sys_context)) = context.get_mut(&String::from("sys")) {
+        let types = if args.is_empty() { vec![] } else { vec![Type::String] };
+        sys_context.set(&String::from("args"), &V::Vector(args.iter().cloned().map(V::String).collect(), types));
+    }
+}
+
+/// the value of an environment variable or `null`
+pub fn sys_env(context: &mut Context, pos: &Position) -> Result<V, E> {
+    match std::env::var(arg_str(context, "name")) {
+        Ok(value) => Ok(V::String(value)),
+        Err(_) => Ok(V::Null)
+    }
+}
+pub fn sys_set_env(context: &mut Context, pos: &Position) -> Result<V, E> {
+    let (name, value) = (arg_str(context, "name"), arg_str(context, "value"));
+    if name.is_empty() || name.contains(['=', '\0']) || value.contains('\0') {
+        context.trace(pos.clone());
+        return Err(E::Error(format!("invalid environment variable {name:?}")))
+    }
+    std::env::set_var(name, value);
+    Ok(V::Null)
+}
+/// stops the script, `_main` exits the process with the code
+pub fn sys_exit(context: &mut Context, pos: &Position) -> Result<V, E> {
+    match context.get(&String::from("code")) {
+        Some(V::Int(code)) => match i32::try_from(*code) {
+            Ok(code) => Err(E::Exit(code)),
+            Err(_) => {
+                let code = *code;
+                context.trace(pos.clone());
+                Err(E::Error(format!("exit code {code} is out of range")))
+            }
+        }
+        _ => Err(E::Exit(0))
+    }
+}
+pub fn sys_cwd(context: &mut Context, pos: &Position) -> Result<V, E> {
+    match std::env::current_dir() {
+        Ok(dir) => Ok(V::String(dir.to_string_lossy().to_string())),
+        Err(e) => {
+            context.trace(pos.clone());
+            Err(E::Error(e.to_string()))
+        }
+    }
+}
+/// runs a program to its end and returns `{ status, stdout, stderr }`,
+/// `status` is `null` if the program was stopped by a signal
+pub fn sys_run(context: &mut Context, pos: &Position) -> Result<V, E> {
+    let cmd = arg_str(context, "cmd");
+    let args = match context.get(&String::from("args")) {
+        Some(V::Vector(args, _)) => args.iter().map(|arg| arg.to_string()).collect(),
+        _ => vec![]
+    };
+    match Command::new(&cmd).args(args).output() {
+        Ok(output) => {
+            let mut result: OrderedMap<String, V> = OrderedMap::new();
+            result.insert("status".into(), output.status.code().map(|code| V::Int(code as i64)).unwrap_or(V::Null));
+            result.insert("stdout".into(), V::String(String::from_utf8_lossy(&output.stdout).to_string()));
+            result.insert("stderr".into(), V::String(String::from_utf8_lossy(&output.stderr).to_string()));
+            Ok(V::Object(result))
+        }
+        Err(e) => {
+            context.trace(pos.clone());
+            Err(E::Process(cmd, e.to_string()))
+        }
+    }
+}
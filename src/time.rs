@@ -0,0 +1,201 @@
+use std::sync::OnceLock;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use crate::*;
+
+const DATE_FIELDS: [&str; 6] = ["year", "month", "day", "hour", "minute", "second"];
+/// the years a date can have, their timestamps stay in the range `time.format` takes
+const YEARS: std::ops::RangeInclusive<i64> = -1_000_000..=1_000_000;
+
+/// the days since 1970-01-01 of a date in the proleptic gregorian calendar
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let doy = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+/// the year, month and day of the days since 1970-01-01
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    (yoe + era * 400 + if month <= 2 { 1 } else { 0 }, month, day)
+}
+fn days_in_month(year: i64, month: i64) -> i64 {
+    days_from_civil(year + month / 12, month % 12 + 1, 1) - days_from_civil(year, month, 1)
+}
+/// the timestamp of a date, `None` if a part is out of range
+fn timestamp(parts: [i64; 6], fraction: f64) -> Option<f64> {
+    let [year, month, day, hour, minute, second] = parts;
+    if !YEARS.contains(&year) || !(1..=12).contains(&month) || day < 1 || day > days_in_month(year, month)
+        || !(0..24).contains(&hour) || !(0..60).contains(&minute) || !(0..60).contains(&second) {
+        return None
+    }
+    let seconds = days_from_civil(year, month, day) * 86400 + hour * 3600 + minute * 60 + second;
+    Some(seconds as f64 + fraction)
+}
+/// the date parts of a timestamp and the fraction of its second
+fn date_parts(t: f64) -> ([i64; 6], f64) {
+    let seconds = t.floor();
+    let (days, rest) = ((seconds as i64).div_euclid(86400), (seconds as i64).rem_euclid(86400));
+    let (year, month, day) = civil_from_days(days);
+    ([year, month, day, rest / 3600, rest % 3600 / 60, rest % 60], t - seconds)
+}
+fn arg_time(context: &mut Context, pos: &Position, id: &str) -> Result<f64, E> {
+    match context.get(&id.to_string()) {
+        Some(V::Int(t)) => Ok(*t as f64),
+        Some(V::Float(t)) if t.is_finite() && t.abs() < 1e15 => Ok(*t),
+        t => {
+            let t = t.cloned().unwrap_or(V::Null);
+            context.trace(pos.clone());
+            Err(E::Date(t.to_string()))
+        }
+    }
+}
+
+/// seconds since 1970-01-01 00:00:00 UTC
+pub fn time_now(context: &mut Context, pos: &Position) -> Result<V, E> {
+    Ok(V::Float(SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs_f64()).unwrap_or(0.0)))
+}
+/// seconds since the first call, never going backwards
+pub fn time_monotonic(context: &mut Context, pos: &Position) -> Result<V, E> {
+    static START: OnceLock<Instant> = OnceLock::new();
+    Ok(V::Float(START.get_or_init(Instant::now).elapsed().as_secs_f64()))
+}
+pub fn time_sleep(context: &mut Context, pos: &Position) -> Result<V, E> {
+    let seconds = match context.get(&String::from("seconds")) {
+        Some(V::Int(v)) => *v as f64,
+        Some(V::Float(v)) => *v,
+        _ => 0.0
+    };
+    if seconds > 0.0 && seconds.is_finite() {
+        std::thread::sleep(Duration::from_secs_f64(seconds));
+    }
+    Ok(V::Null)
+}
+/// the UTC date of a timestamp as `{ year, month, day, hour, minute, second, weekday }`,
+/// `second` keeps the fraction and `weekday` goes from 1 (monday) to 7
+pub fn time_date(context: &mut Context, pos: &Position) -> Result<V, E> {
+    let t = arg_time(context, pos, "t")?;
+    let (parts, fraction) = date_parts(t);
+    let mut date: OrderedMap<String, V> = OrderedMap::new();
+    for (name, part) in DATE_FIELDS.iter().zip(parts) {
+        date.insert(name.to_string(), V::Int(part));
+    }
+    if fraction > 0.0 {
+        date.insert("second".into(), V::Float(parts[5] as f64 + fraction));
+    }
+    let days = days_from_civil(parts[0], parts[1], parts[2]);
+    date.insert("weekday".into(), V::Int((days + 3).rem_euclid(7) + 1));
+    Ok(V::Object(date))
+}
+/// the timestamp of a UTC date, missing fields are the start of their range
+pub fn time_timestamp(context: &mut Context, pos: &Position) -> Result<V, E> {
+    let date = match context.get(&String::from("date")) {
+        Some(V::Object(date)) => date.clone(),
+        _ => OrderedMap::new()
+    };
+    let mut parts = [1970, 1, 1, 0, 0, 0];
+    let mut fraction = 0.0;
+    for (i, name) in DATE_FIELDS.iter().enumerate() {
+        match date.get(*name) {
+            Some(V::Int(v)) => parts[i] = *v,
+            Some(V::Float(v)) if *name == "second" && v.is_finite() => {
+                parts[i] = v.floor() as i64;
+                fraction = v - v.floor();
+            }
+            None if i > 0 => parts[i] = if i < 3 { 1 } else { 0 },
+            _ => {
+                context.trace(pos.clone());
+                return Err(E::Date(V::Object(date).to_string()))
+            }
+        }
+    }
+    match timestamp(parts, fraction) {
+        Some(t) => Ok(V::Float(t)),
+        None => {
+            context.trace(pos.clone());
+            Err(E::Date(V::Object(date).to_string()))
+        }
+    }
+}
+/// ISO-8601 in UTC like `2024-03-01T12:30:00Z`, with milliseconds if the timestamp has a fraction
+pub fn time_format(context: &mut Context, pos: &Position) -> Result<V, E> {
+    let t = arg_time(context, pos, "t")?;
+    let (mut parts, fraction) = date_parts(t);
+    let mut millis = (fraction * 1000.0).round() as i64;
+    if millis == 1000 {
+        parts = date_parts(t.floor() + 1.0).0;
+        millis = 0;
+    }
+    let [year, month, day, hour, minute, second] = parts;
+    let mut s = format!("{year:04}-{month:02}-{day:02}T{hour:02}:{minute:02}:{second:02}");
+    if fraction > 0.0 { s.push_str(format!(".{millis:03}").as_str()) }
+    s.push('Z');
+    Ok(V::String(s))
+}
+/// the timestamp of an ISO-8601 date `YYYY-MM-DD` with an optional time `THH:MM[:SS[.fff]]`
+/// and offset `Z` or `+HH:MM`, without an offset the time is in UTC
+pub fn time_parse(context: &mut Context, pos: &Position) -> Result<V, E> {
+    let s = match context.get(&String::from("s")) {
+        Some(V::String(s)) => s.clone(),
+        _ => String::new()
+    };
+    match parse_iso(&s) {
+        Some(t) => Ok(V::Float(t)),
+        None => {
+            context.trace(pos.clone());
+            Err(E::Date(s))
+        }
+    }
+}
+fn parse_iso(s: &str) -> Option<f64> {
+    fn number(s: &str, digits: usize) -> Option<i64> {
+        if s.len() != digits || !s.chars().all(|c| c.is_ascii_digit()) { return None }
+        s.parse().ok()
+    }
+    let (date, time) = match s.split_once(['T', ' ']) {
+        Some((date, time)) => (date, Some(time)),
+        None => (s, None)
+    };
+    let mut date_parts = date.split('-');
+    let year = number(date_parts.next()?, 4)?;
+    let month = number(date_parts.next()?, 2)?;
+    let day = number(date_parts.next()?, 2)?;
+    if date_parts.next().is_some() { return None }
+    let (mut hour, mut minute, mut second, mut fraction, mut offset) = (0, 0, 0, 0.0, 0);
+    if let Some(time) = time {
+        let (clock, zone) = match time.find(['Z', '+', '-']) {
+            Some(i) => time.split_at(i),
+            None => (time, "")
+        };
+        let (clock, fraction_digits) = match clock.split_once('.') {
+            Some((clock, digits)) => (clock, Some(digits)),
+            None => (clock, None)
+        };
+        let mut clock_parts = clock.split(':');
+        hour = number(clock_parts.next()?, 2)?;
+        minute = number(clock_parts.next()?, 2)?;
+        if let Some(part) = clock_parts.next() { second = number(part, 2)? }
+        if clock_parts.next().is_some() { return None }
+        if let Some(digits) = fraction_digits {
+            if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) { return None }
+            fraction = format!("0.{digits}").parse().ok()?;
+        }
+        match zone {
+            "" | "Z" => {}
+            _ => {
+                let sign = if zone.starts_with('-') { -1 } else { 1 };
+                let (h, m) = zone[1..].split_once(':')?;
+                offset = sign * (number(h, 2)? * 3600 + number(m, 2)? * 60);
+            }
+        }
+    }
+    Some(timestamp([year, month, day, hour, minute, second], fraction)? - offset as f64)
+}
@@ -1,18 +1,23 @@
-use std::{collections::{HashSet, HashMap}, hash::Hash};
+use std::{cell::RefCell, collections::HashSet, hash::Hash, rc::Rc};
 use crate::*;
 
 pub type ProcFn = fn(&mut Context, &Position) -> Result<V, E>;
-pub type ProcValueParams = Vec<(String, Option<Node>, bool)>;
-pub type Rules = Vec<(Node, Option<Node>)>;
+/// name, type, whether arguments are cast to the type and whether the param collects the rest of the arguments
+pub type ProcValueParams = Vec<(String, Option<Node>, bool, bool)>;
+pub type Rules = Vec<(Node, Option<Node>, String)>;
+pub type ShapeFields = Vec<(String, V, bool)>;
 
 #[derive(Clone)]
 pub enum V {
     Wildcard, Null,
-    Int(i64), Float(f64), Bool(bool), String(String),
-    Tuple(Vec<V>), Vector(Vec<V>, Vec<Type>), Object(HashMap<String, V>), Container(Context),
+    Int(i64), BigInt(BigInt), Float(f64), Bool(bool), String(String), Bytes(Vec<u8>),
+    // start, end (never included) and step, which isn't 0
+    Range(i64, i64, i64),
+    Tuple(Vec<V>), Vector(Vec<V>, Vec<Type>), Object(OrderedMap<String, V>),
+    Set(OrderedMap<Key, ()>), Map(OrderedMap<Key, V>), Container(Context),
     Proc(ProcValueParams, Node), ForeignProc(ProcValueParams, ProcFn),
-    Rule(String, String, Rules),
-    Type(Type)
+    Rule(String, Vec<String>, Rules, Vec<V>, String), RuleOp(T, Vec<V>), Shape(String, ShapeFields),
+    Type(Type), Socket(Rc<RefCell<Socket>>)
 }
 impl std::fmt::Display for V {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -20,17 +25,24 @@ impl std::fmt::Display for V {
             Self::Wildcard => write!(f, "_"),
             Self::Null => write!(f, "null"),
             Self::Int(v) => write!(f, "{v}"),
+            Self::BigInt(v) => write!(f, "{v}"),
             Self::Float(v) => write!(f, "{v}"),
             Self::Bool(v) => write!(f, "{v}"),
             Self::String(v) => write!(f, "{v}"),
+            Self::Bytes(v) => write!(f, "{}", bytes_repr(v)),
+            Self::Range(_, _, _) => write!(f, "{self:?}"),
             Self::Tuple(v) => write!(f, "({})", v.iter().map(|x| x.to_string()).collect::<Vec<String>>().join(", ")),
             Self::Vector(v, _) => write!(f, "{v:?}"),
             Self::Object(v) => write!(f, "{{ {} }}", v.iter().map(|(k, v)| format!("{k} = {v}")).collect::<Vec<String>>().join(", ")),
+            Self::Set(_) | Self::Map(_) => write!(f, "{self:?}"),
             Self::Container(context) => write!(f, "container:{:?}", context as *const Context),
             Self::Proc(_, body) => write!(f, "proc:{:?}", body as *const Node),
             Self::ForeignProc(_, func) => write!(f, "foreign-proc:{:?}", func as *const ProcFn),
-            Self::Rule(name, _, rules) => write!(f, "{name}-rule:{:?}", rules as *const Rules),
+            Self::Rule(name, _, rules, _, _) => write!(f, "{name}-rule:{:?}", rules as *const Rules),
+            Self::RuleOp(_, _) => write!(f, "{}", self.typ()),
+            Self::Shape(name, fields) => write!(f, "{name}-shape:{:?}", fields as *const ShapeFields),
             Self::Type(v) => write!(f, "{v}"),
+            Self::Socket(socket) => write!(f, "{:?}", socket.borrow()),
         }
     }
 }
@@ -40,17 +52,28 @@ impl std::fmt::Debug for V {
             Self::Wildcard => write!(f, "_"),
             Self::Null => write!(f, "null"),
             Self::Int(v) => write!(f, "{v:?}"),
+            Self::BigInt(v) => write!(f, "{v}"),
             Self::Float(v) => write!(f, "{v:?}"),
             Self::Bool(v) => write!(f, "{v:?}"),
             Self::String(v) => write!(f, "{v:?}"),
+            Self::Bytes(v) => write!(f, "{}", bytes_repr(v)),
+            Self::Range(start, end, 1) => write!(f, "{start}..{end}"),
+            Self::Range(start, end, step) => write!(f, "{start}..{end}..{step}"),
             Self::Tuple(v) => write!(f, "({})", v.iter().map(|x| format!("{x:?}")).collect::<Vec<String>>().join(", ")),
             Self::Vector(v, _) => write!(f, "{v:?}"),
             Self::Object(v) => write!(f, "{{ {} }}", v.iter().map(|(k, v)| format!("{k} = {v:?}")).collect::<Vec<String>>().join(", ")),
+            Self::Set(v) if v.is_empty() => write!(f, "set()"),
+            Self::Set(v) => write!(f, "{{{}}}", v.keys().map(|k| format!("{:?}", k.value())).collect::<Vec<String>>().join(", ")),
+            Self::Map(v) if v.is_empty() => write!(f, "map()"),
+            Self::Map(v) => write!(f, "{{{}}}", v.iter().map(|(k, v)| format!("{:?}: {v:?}", k.value())).collect::<Vec<String>>().join(", ")),
             Self::Container(context) => write!(f, "container:{:?}", context as *const Context),
             Self::Proc(_, body) => write!(f, "proc:{:?}", body as *const Node),
             Self::ForeignProc(_, func) => write!(f, "foreign-proc:{:?}", func as *const ProcFn),
-            Self::Rule(name, _, rules) => write!(f, "{name}-rule:{:?}", rules as *const Rules),
+            Self::Rule(name, _, rules, _, _) => write!(f, "{name}-rule:{:?}", rules as *const Rules),
+            Self::RuleOp(_, _) => write!(f, "{}", self.typ()),
+            Self::Shape(name, fields) => write!(f, "{name}-shape:{:?}", fields as *const ShapeFields),
             Self::Type(v) => write!(f, "{v:?}"),
+            Self::Socket(socket) => write!(f, "{:?}", socket.borrow()),
         }
     }
 }
@@ -65,12 +88,21 @@ impl PartialEq for V {
             }
             Self::Int(v1) => match other {
                 Self::Int(v2) => *v1 == *v2,
+                Self::BigInt(v2) => &BigInt::from_i64(*v1) == v2,
                 Self::Float(v2) => *v1 as f64 == *v2,
                 Self::Wildcard => true,
                 _ => false
             }
+            Self::BigInt(v1) => match other {
+                Self::BigInt(v2) => v1 == v2,
+                Self::Int(v2) => v1 == &BigInt::from_i64(*v2),
+                Self::Float(v2) => v1.to_f64() == *v2,
+                Self::Wildcard => true,
+                _ => false
+            }
             Self::Float(v1) => match other {
                 Self::Int(v2) => *v1 == *v2 as f64,
+                Self::BigInt(v2) => *v1 == v2.to_f64(),
                 Self::Float(v2) => *v1 == *v2,
                 Self::Wildcard => true,
                 _ => false
@@ -85,6 +117,16 @@ impl PartialEq for V {
                 Self::Wildcard => true,
                 _ => false
             }
+            Self::Bytes(v1) => match other {
+                Self::Bytes(v2) => v1 == v2,
+                Self::Wildcard => true,
+                _ => false
+            }
+            Self::Range(start1, end1, step1) => match other {
+                Self::Range(start2, end2, step2) => (start1, end1, step1) == (start2, end2, step2),
+                Self::Wildcard => true,
+                _ => false
+            }
             Self::Tuple(v1) => match other {
                 Self::Tuple(v2) => v1 == v2,
                 Self::Wildcard => true,
@@ -100,6 +142,16 @@ impl PartialEq for V {
                 Self::Wildcard => true,
                 _ => false
             }
+            Self::Set(v1) => match other {
+                Self::Set(v2) => v1 == v2,
+                Self::Wildcard => true,
+                _ => false
+            }
+            Self::Map(v1) => match other {
+                Self::Map(v2) => v1 == v2,
+                Self::Wildcard => true,
+                _ => false
+            }
             Self::Container(c1) => match other {
                 Self::Container(c2) => c1 as *const Context == c2 as *const Context,
                 Self::Wildcard => true,
@@ -115,9 +167,20 @@ impl PartialEq for V {
                 Self::Wildcard => true,
                 _ => false
             }
-            Self::Rule(name1, _, rules1) => match other {
-                Self::Rule(name2, _, rules2) => name1 == name2 &&
-                (rules1 as *const Rules) == (rules2 as *const Rules),
+            Self::Rule(name1, params1, _, args1, _) => match other {
+                Self::Rule(name2, params2, _, args2, _) => name1 == name2 && params1 == params2 && args1 == args2,
+                Self::Wildcard => true,
+                _ => false
+            }
+            Self::RuleOp(op1, values1) => match other {
+                Self::RuleOp(op2, values2) => op1 == op2 && values1.len() == values2.len() &&
+                values1.iter().all(|v| values2.contains(v)),
+                Self::Wildcard => true,
+                _ => false
+            }
+            Self::Shape(name1, fields1) => match other {
+                Self::Shape(name2, fields2) => name1 == name2 && fields1.len() == fields2.len() &&
+                fields1.iter().zip(fields2.iter()).all(|((k1, _, o1), (k2, _, o2))| k1 == k2 && o1 == o2),
                 Self::Wildcard => true,
                 _ => false
             }
@@ -125,6 +188,11 @@ impl PartialEq for V {
                 Self::Type(v2) => v1 == v2,
                 _ => false
             }
+            Self::Socket(s1) => match other {
+                Self::Socket(s2) => Rc::ptr_eq(s1, s2),
+                Self::Wildcard => true,
+                _ => false
+            }
         }
     }
 }
@@ -134,22 +202,73 @@ impl V {
             Self::Wildcard => Type::Any,
             Self::Null => Type::Undefined,
             Self::Int(_) => Type::Int,
+            Self::BigInt(_) => Type::BigInt,
             Self::Float(_) => Type::Float,
             Self::Bool(_) => Type::Bool,
             Self::String(_) => Type::String,
+            Self::Bytes(_) => Type::Bytes,
+            Self::Range(_, _, _) => Type::Range,
             Self::Tuple(v) => Type::Tuple(v.iter().map(|x| x.typ()).collect()),
             Self::Vector(_, t) => Type::Vector(t.clone()),
-            Self::Object(_) => Type::Object,
+            Self::Object(v) => Type::Object(Self::create_union(v.values().cloned().collect())),
+            Self::Set(v) => Type::Set(Self::create_union(v.keys().map(Key::value).collect())),
+            Self::Map(v) => Type::Map(Box::new((
+                Self::create_union(v.keys().map(Key::value).collect()), Self::create_union(v.values().cloned().collect())
+            ))),
             Self::Container(_) => Type::Container,
             Self::Proc(_, _) => Type::Proc,
             Self::ForeignProc(_, _) => Type::ForeignProc,
-            Self::Rule(name, _, _) => Type::Rule(name.clone()),
+            Self::Rule(name, _, _, _, _) => Type::Rule(name.clone()),
+            Self::RuleOp(op, values) => match op {
+                T::And => Type::RuleAnd(values.iter().map(|v| v.constraint_type()).collect()),
+                T::Or => Type::RuleOr(values.iter().map(|v| v.constraint_type()).collect()),
+                _ => Type::RuleNot(Box::new(values[0].constraint_type())),
+            }
+            Self::Shape(name, _) => Type::Shape(name.clone()),
             Self::Type(_) => Type::Type,
+            Self::Socket(_) => Type::Socket,
         }
     }
     pub fn bool(value: &V) -> Self {
         Type::Bool.cast(value).unwrap_or_else(|| Self::Bool(false))
     }
+    pub fn is_constraint(&self) -> bool {
+        matches!(self, Self::Type(_) | Self::Rule(_, _, _, _, _) | Self::RuleOp(_, _) | Self::Shape(_, _))
+    }
+    /// the value read where a type is expected, a tuple of types like `(int, str)` is the tuple type
+    pub fn into_type(self) -> Self {
+        match self.tuple_type() {
+            Some(typ) => Self::Type(typ),
+            None => self
+        }
+    }
+    fn tuple_type(&self) -> Option<Type> {
+        match self {
+            Self::Tuple(values) if !values.is_empty() => values.iter().map(|v| match v {
+                Self::Type(typ) => Some(typ.clone()),
+                _ => v.tuple_type()
+            }).collect::<Option<Vec<Type>>>().map(Type::Tuple),
+            _ => None
+        }
+    }
+    /// the type a constraint stands for, a type value stands for itself
+    pub fn constraint_type(&self) -> Type {
+        match self {
+            Self::Type(typ) => typ.clone(),
+            _ => self.typ()
+        }
+    }
+    /// combines two constraints with `and`/`or`, flattening nested operations of the same kind
+    pub fn rule_op(op: T, left: &Self, right: &Self) -> Self {
+        let mut values: Vec<Self> = vec![];
+        for v in [left, right] {
+            match v {
+                Self::RuleOp(op_, sub_values) if op_ == &op => values.extend(sub_values.iter().cloned()),
+                _ => values.push(v.clone())
+            }
+        }
+        Self::RuleOp(op, values)
+    }
     pub fn create_union(values: Vec<Self>) -> Vec<Type> {
         let mut collected: Vec<Type> = vec![];
         for v in values {
@@ -161,13 +280,43 @@ impl V {
     }
 }
 
+/// the values which can be elements of a `set` or keys of a `map`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Key {
+    Null, Int(i64), Bool(bool), String(String), Bytes(Vec<u8>), Tuple(Vec<Key>)
+}
+impl Key {
+    /// the key of a value, `None` if the value can't be hashed (like a `float` or a `vec`)
+    pub fn from(value: &V) -> Option<Self> {
+        match value {
+            V::Null => Some(Self::Null),
+            V::Int(v) => Some(Self::Int(*v)),
+            V::Bool(v) => Some(Self::Bool(*v)),
+            V::String(v) => Some(Self::String(v.clone())),
+            V::Bytes(v) => Some(Self::Bytes(v.clone())),
+            V::Tuple(values) => values.iter().map(Self::from).collect::<Option<Vec<Self>>>().map(Self::Tuple),
+            _ => None
+        }
+    }
+    pub fn value(&self) -> V {
+        match self {
+            Self::Null => V::Null,
+            Self::Int(v) => V::Int(*v),
+            Self::Bool(v) => V::Bool(*v),
+            Self::String(v) => V::String(v.clone()),
+            Self::Bytes(v) => V::Bytes(v.clone()),
+            Self::Tuple(keys) => V::Tuple(keys.iter().map(Self::value).collect()),
+        }
+    }
+}
+
 #[derive(Clone)]
 pub enum Type {
     Any, Undefined,
-    Int, Float, Bool, String,
-    Tuple(Vec<Type>), Vector(Vec<Type>), Object, Container,
-    Proc, ForeignProc, Rule(String),
-    Type,
+    Int, BigInt, Float, Bool, String, Bytes, Range,
+    Tuple(Vec<Type>), Vector(Vec<Type>), Object(Vec<Type>), Set(Vec<Type>), Map(Box<(Vec<Type>, Vec<Type>)>), Container,
+    Proc, ForeignProc, Rule(String), RuleAnd(Vec<Type>), RuleOr(Vec<Type>), RuleNot(Box<Type>), Shape(String),
+    Type, Socket,
     Union(Vec<Type>), Scission(Vec<Type>)
 }
 impl Type {
@@ -218,6 +367,7 @@ impl Type {
             Type::Undefined => Some(V::Null),
             Type::Int => match value {
                 V::Int(_) => Some(value.clone()),
+                V::BigInt(v) => v.to_i64().map(V::Int),
                 V::Float(v) => Some(V::Int(*v as i64)),
                 V::Bool(v) => Some(V::Int(*v as i64)),
                 V::Wildcard | V::Null => Some(V::Int(0)),
@@ -227,9 +377,19 @@ impl Type {
                 }
                 _ => None
             }
+            Type::BigInt => match value {
+                V::BigInt(_) => Some(value.clone()),
+                V::Int(v) => Some(V::BigInt(BigInt::from_i64(*v))),
+                V::Float(v) => BigInt::from_f64(*v).map(V::BigInt),
+                V::Bool(v) => Some(V::BigInt(BigInt::from_i64(*v as i64))),
+                V::Wildcard | V::Null => Some(V::BigInt(BigInt::from_i64(0))),
+                V::String(v) => BigInt::parse(v.trim()).map(V::BigInt),
+                _ => None
+            }
             Type::Float => match value {
                 V::Float(_) => Some(value.clone()),
                 V::Int(v) => Some(V::Float(*v as f64)),
+                V::BigInt(v) => Some(V::Float(v.to_f64())),
                 V::Bool(v) => Some(V::Float(*v as u8 as f64)),
                 V::Wildcard | V::Null => Some(V::Float(0.0)),
                 V::String(v) => match v.parse::<f64>() {
@@ -241,6 +401,7 @@ impl Type {
             Type::Bool => match value {
                 V::Bool(_) => Some(value.clone()),
                 V::Int(v) => Some(V::Bool(*v == 0)),
+                V::BigInt(v) => Some(V::Bool(v.is_zero())),
                 V::Float(v) => Some(V::Bool(*v == 0.0)),
                 V::Null => Some(V::Bool(false)),
                 V::String(v) => match v.parse::<bool>() {
@@ -250,24 +411,229 @@ impl Type {
                 _ => Some(V::Bool(true))
             }
             Type::String => Some(V::String(value.to_string())),
-            Type::Tuple(typ) => match value {
-                V::Tuple(_) => Some(value.clone()),
+            Type::Bytes => match value {
+                V::Bytes(_) => Some(value.clone()),
+                V::String(v) => Some(V::Bytes(v.as_bytes().to_vec())),
+                V::Vector(values, _) => values.iter().map(|v| match v {
+                    V::Int(b) => u8::try_from(*b).ok(),
+                    _ => None
+                }).collect::<Option<Vec<u8>>>().map(V::Bytes),
                 _ => None
             }
-            Type::Vector(typ) => match value {
-                V::Vector(_, _) => Some(value.clone()),
+            Type::Range => match value {
+                V::Range(_, _, _) => Some(value.clone()),
                 _ => None
             }
-            Type::Object => None,
+            Type::Tuple(types) => match value {
+                V::Tuple(_) if types.is_empty() => Some(value.clone()),
+                V::Tuple(values) if values.len() == types.len() => {
+                    let mut new_values: Vec<V> = vec![];
+                    for (typ, v) in types.iter().zip(values.iter()) {
+                        new_values.push(typ.cast(v)?);
+                    }
+                    Some(V::Tuple(new_values))
+                }
+                _ => None
+            }
+            Type::Vector(types) => match value {
+                V::Bytes(bytes) => self.cast(&V::Vector(bytes.iter().map(|b| V::Int(*b as i64)).collect(), vec![Type::Int])),
+                // the length is checked first, a range too long to hold in memory can't be cast
+                V::Range(start, end, step) => {
+                    let mut values: Vec<V> = vec![];
+                    values.try_reserve_exact(usize::try_from(range_len(*start, *end, *step)).ok()?).ok()?;
+                    values.extend(range_values(*start, *end, *step).map(V::Int));
+                    self.cast(&V::Vector(values, vec![Type::Int]))
+                }
+                V::Set(keys) => self.cast(&V::Vector(keys.keys().map(Key::value).collect(), vec![])),
+                V::Map(entries) => self.cast(&V::Vector(entries.iter().map(|(k, v)| V::Tuple(vec![k.value(), v.clone()])).collect(), vec![])),
+                V::Vector(values, _) => {
+                    let typ = Self::element(types);
+                    let mut new_values: Vec<V> = vec![];
+                    for v in values.iter() {
+                        new_values.push(typ.cast(v)?);
+                    }
+                    let new_types = V::create_union(new_values.clone());
+                    Some(V::Vector(new_values, new_types))
+                }
+                _ => None
+            }
+            Type::Object(types) => match value {
+                V::Object(fields) => {
+                    let typ = Self::element(types);
+                    let mut new_fields: OrderedMap<String, V> = OrderedMap::new();
+                    for (k, v) in fields.iter() {
+                        new_fields.insert(k.clone(), typ.cast(v)?);
+                    }
+                    Some(V::Object(new_fields))
+                }
+                _ => None
+            }
+            // `set()` is an empty set, values which can't be keys can't be cast
+            Type::Set(types) => {
+                let values = match value {
+                    V::Null => vec![],
+                    V::Set(keys) => keys.keys().map(Key::value).collect(),
+                    V::Vector(values, _) | V::Tuple(values) => values.clone(),
+                    _ => return None
+                };
+                let typ = Self::element(types);
+                let mut keys: OrderedMap<Key, ()> = OrderedMap::new();
+                for v in values.iter() {
+                    keys.insert(Key::from(&typ.cast(v)?)?, ());
+                }
+                Some(V::Set(keys))
+            }
+            // `map()` is an empty map, a `vec` is cast from `(key, value)` tuples
+            Type::Map(types) => {
+                let entries: Vec<(V, V)> = match value {
+                    V::Null => vec![],
+                    V::Map(entries) => entries.iter().map(|(k, v)| (k.value(), v.clone())).collect(),
+                    V::Object(fields) => fields.iter().map(|(k, v)| (V::String(k.clone()), v.clone())).collect(),
+                    V::Vector(values, _) => values.iter().map(|v| match v {
+                        V::Tuple(pair) if pair.len() == 2 => Some((pair[0].clone(), pair[1].clone())),
+                        _ => None
+                    }).collect::<Option<Vec<(V, V)>>>()?,
+                    _ => return None
+                };
+                let (key_type, value_type) = (Self::element(&types.0), Self::element(&types.1));
+                let mut new_entries: OrderedMap<Key, V> = OrderedMap::new();
+                for (k, v) in entries.iter() {
+                    new_entries.insert(Key::from(&key_type.cast(k)?)?, value_type.cast(v)?);
+                }
+                Some(V::Map(new_entries))
+            }
             Type::Container => None,
             Type::Proc => None,
             Type::ForeignProc => None,
             Type::Rule(_) => None,
+            Type::RuleAnd(_) | Type::RuleOr(_) | Type::RuleNot(_) => None,
+            Type::Shape(_) => None,
             Type::Type => Some(V::Type(value.typ())),
-            Type::Union(_) => None,
+            Type::Socket => match value {
+                V::Socket(_) => Some(value.clone()),
+                _ => None
+            }
+            // a member the value already is wins over the first member it can be cast to
+            Type::Union(types) => match types.iter().find(|typ| typ.check(value).is_ok()) {
+                Some(_) => Some(value.clone()),
+                None => types.iter().find_map(|typ| typ.cast(value))
+            }
             Type::Scission(_) => None,
         }
     }
+    /// the type of the elements of a vector or object with the given element types
+    fn element(types: &[Self]) -> Self {
+        match types.len() {
+            0 => Self::Any,
+            1 => types[0].clone(),
+            _ => Self::Union(types.to_vec())
+        }
+    }
+    /// finds the first part of the value which doesn't match the type, returning its path
+    /// (like `[2]` or `.name`), the expected type and the part itself
+    pub fn mismatch(&self, value: &V) -> Option<(String, Self, V)> {
+        let elements: Vec<(String, &V)> = match (self, value) {
+            (Self::Any, _) => return None,
+            (Self::Tuple(types), V::Tuple(values)) if !types.is_empty() => {
+                if types.len() != values.len() {
+                    return Some((String::new(), self.clone(), value.clone()))
+                }
+                for (i, (typ, v)) in types.iter().zip(values.iter()).enumerate() {
+                    if let Some((path, typ, v)) = typ.mismatch(v) {
+                        return Some((format!("[{i}]{path}"), typ, v))
+                    }
+                }
+                return None
+            }
+            (Self::Vector(_), V::Vector(values, _)) =>
+                values.iter().enumerate().map(|(i, v)| (format!("[{i}]"), v)).collect(),
+            (Self::Object(_), V::Object(fields)) =>
+                fields.iter().map(|(k, v)| (format!(".{k}"), v)).collect(),
+            (Self::Set(types), V::Set(keys)) => {
+                let typ = Self::element(types);
+                for k in keys.keys() {
+                    if let Some((path, typ, v)) = typ.mismatch(&k.value()) {
+                        return Some((format!("{{{:?}}}{path}", k.value()), typ, v))
+                    }
+                }
+                return None
+            }
+            (Self::Map(types), V::Map(entries)) => {
+                let (key_type, value_type) = (Self::element(&types.0), Self::element(&types.1));
+                for (k, v) in entries.iter() {
+                    let mismatch = key_type.mismatch(&k.value()).or_else(|| value_type.mismatch(v));
+                    if let Some((path, typ, v)) = mismatch {
+                        return Some((format!("[{:?}]{path}", k.value()), typ, v))
+                    }
+                }
+                return None
+            }
+            (Self::Union(types), _) => return if types.iter().any(|typ| typ.mismatch(value).is_none()) {
+                None
+            } else {
+                Some((String::new(), self.clone(), value.clone()))
+            },
+            _ => return if self == &value.typ() {
+                None
+            } else {
+                Some((String::new(), self.clone(), value.clone()))
+            }
+        };
+        let typ = match self {
+            Self::Vector(types) | Self::Object(types) => Self::element(types),
+            _ => Self::Any
+        };
+        for (index, v) in elements {
+            if let Some((path, typ, v)) = typ.mismatch(v) {
+                return Some((format!("{index}{path}"), typ, v))
+            }
+        }
+        None
+    }
+    /// like `mismatch`, but finds the first part of the value which can't be cast to the type
+    pub fn cast_mismatch(&self, value: &V) -> Option<(String, Self, V)> {
+        if self.cast(value).is_some() { return None }
+        let elements: Vec<(String, &V)> = match (self, value) {
+            (Self::Tuple(types), V::Tuple(values)) if types.len() == values.len() => {
+                for (i, (typ, v)) in types.iter().zip(values.iter()).enumerate() {
+                    if let Some((path, typ, v)) = typ.cast_mismatch(v) {
+                        return Some((format!("[{i}]{path}"), typ, v))
+                    }
+                }
+                return None
+            }
+            (Self::Vector(_), V::Vector(values, _)) =>
+                values.iter().enumerate().map(|(i, v)| (format!("[{i}]"), v)).collect(),
+            (Self::Object(_), V::Object(fields)) =>
+                fields.iter().map(|(k, v)| (format!(".{k}"), v)).collect(),
+            _ => return Some((String::new(), self.clone(), value.clone()))
+        };
+        let typ = match self {
+            Self::Vector(types) | Self::Object(types) => Self::element(types),
+            _ => Self::Any
+        };
+        for (index, v) in elements {
+            if let Some((path, typ, v)) = typ.cast_mismatch(v) {
+                return Some((format!("{index}{path}"), typ, v))
+            }
+        }
+        None
+    }
+    /// checks the value against the type, naming the first element that doesn't match
+    pub fn check(&self, value: &V) -> Result<(), E> {
+        match self.mismatch(value) {
+            None => Ok(()),
+            Some((path, typ, v)) if path.is_empty() => Err(E::ExpectedType(typ, v.typ())),
+            Some((path, typ, v)) => Err(E::ExpectedElement(path, typ, v.typ()))
+        }
+    }
+    /// the error for a failed `cast`, naming the first element that couldn't be cast
+    pub fn cast_error(&self, value: &V) -> E {
+        match self.cast_mismatch(value) {
+            Some((path, typ, v)) if !path.is_empty() => E::CastElement(path, typ, v),
+            _ => E::Cast(self.clone(), value.clone())
+        }
+    }
     pub fn vector(&self) -> Self {
         if let Self::Union(types) = self {
             Self::Vector(types.clone())
@@ -275,22 +641,47 @@ impl Type {
             Self::Vector(vec![self.clone()])
         }
     }
+    pub fn object(&self) -> Self {
+        if let Self::Union(types) = self {
+            Self::Object(types.clone())
+        } else {
+            Self::Object(vec![self.clone()])
+        }
+    }
+    pub fn set(&self) -> Self {
+        Self::Set(self.members())
+    }
+    pub fn map(&self, value_type: &Self) -> Self {
+        Self::Map(Box::new((self.members(), value_type.members())))
+    }
+    /// the types of a union, or the type itself
+    fn members(&self) -> Vec<Self> {
+        if let Self::Union(types) = self { types.clone() } else { vec![self.clone()] }
+    }
     pub fn name(&self) -> &str {
         match self {
             Self::Any => "any",
             Self::Undefined => "undefined",
             Self::Int => "int",
+            Self::BigInt => "bigint",
             Self::Float => "float",
             Self::Bool => "bool",
             Self::String => "str",
+            Self::Bytes => "bytes",
+            Self::Range => "range",
             Self::Tuple(_) => "tuple",
             Self::Vector(_) => "vec",
-            Self::Object => "obj",
+            Self::Object(_) => "obj",
+            Self::Set(_) => "set",
+            Self::Map(_) => "map",
             Self::Container => "container",
             Self::Proc => "proc",
             Self::ForeignProc => "foreign_proc",
             Self::Rule(id) => id.as_str(),
+            Self::RuleAnd(_) | Self::RuleOr(_) | Self::RuleNot(_) => "rule",
+            Self::Shape(id) => id.as_str(),
             Self::Type => "type",
+            Self::Socket => "socket",
             Self::Union(_) => "union",
             Self::Scission(_) => "scission",
         }
@@ -307,17 +698,29 @@ impl std::fmt::Debug for Type {
             Self::Any => write!(f, "any"),
             Self::Undefined => write!(f, "undefined"),
             Self::Int => write!(f, "int"),
+            Self::BigInt => write!(f, "bigint"),
             Self::Float => write!(f, "float"),
             Self::Bool => write!(f, "bool"),
             Self::String => write!(f, "str"),
+            Self::Bytes => write!(f, "bytes"),
+            Self::Range => write!(f, "range"),
             Self::Tuple(types) => write!(f, "({})", types.iter().map(|x| x.to_string()).collect::<Vec<String>>().join(", ")),
             Self::Vector(types) => write!(f, "vec[{}]", types.iter().map(|x| x.to_string()).collect::<Vec<String>>().join("|")),
-            Self::Object => write!(f, "obj"),
+            Self::Object(types) => write!(f, "obj[{}]", types.iter().map(|x| x.to_string()).collect::<Vec<String>>().join("|")),
+            Self::Set(types) => write!(f, "set[{}]", types.iter().map(|x| x.to_string()).collect::<Vec<String>>().join("|")),
+            Self::Map(types) => write!(f, "map[{}, {}]",
+                types.0.iter().map(|x| x.to_string()).collect::<Vec<String>>().join("|"),
+                types.1.iter().map(|x| x.to_string()).collect::<Vec<String>>().join("|")),
             Self::Container => write!(f, "container"),
             Self::Proc => write!(f, "proc"),
             Self::ForeignProc => write!(f, "foreign-proc"),
             Self::Rule(name) => write!(f, "{name}-rule"),
+            Self::RuleAnd(types) => write!(f, "({})", types.iter().map(|x| x.to_string()).collect::<Vec<String>>().join(" and ")),
+            Self::RuleOr(types) => write!(f, "({})", types.iter().map(|x| x.to_string()).collect::<Vec<String>>().join(" or ")),
+            Self::RuleNot(typ) => write!(f, "not {typ}"),
+            Self::Shape(name) => write!(f, "{name}-shape"),
             Self::Type => write!(f, "type"),
+            Self::Socket => write!(f, "socket"),
             Self::Union(types) => write!(f, "{}", types.iter().map(|x| x.to_string()).collect::<Vec<String>>().join("|")),
             Self::Scission(types) => write!(f, "scission[{}]", types.iter().map(|x| x.to_string()).collect::<Vec<String>>().join("|")),
         }
@@ -341,6 +744,13 @@ impl PartialEq for Type {
                 Self::Scission(_) => other == self,
                 _ => false
             }
+            Self::BigInt => match other {
+                Self::BigInt => true,
+                Self::Any => true,
+                Self::Union(_) => other == self,
+                Self::Scission(_) => other == self,
+                _ => false
+            }
             Self::Float => match other {
                 Self::Float => true,
                 Self::Any => true,
@@ -363,7 +773,7 @@ impl PartialEq for Type {
                 _ => false
             }
             Self::Tuple(t1) => match other {
-                Self::Tuple(t2) => t1 == t2,
+                Self::Tuple(t2) => t1.is_empty() || t1 == t2,
                 Self::Any => true,
                 Self::Union(_) => other == self,
                 Self::Scission(_) => other == self,
@@ -381,8 +791,28 @@ impl PartialEq for Type {
                 Self::Scission(_) => other == self,
                 _ => false
             }
-            Self::Object => match other {
-                Self::Object => true,
+            Self::Object(t1) => match other {
+                Self::Object(t2) => {
+                    for type2 in t2.iter() {
+                        if !t1.contains(type2) { return false }
+                    }
+                    true
+                }
+                Self::Any => true,
+                Self::Union(_) => other == self,
+                Self::Scission(_) => other == self,
+                _ => false
+            }
+            Self::Set(t1) => match other {
+                Self::Set(t2) => t2.iter().all(|type2| t1.contains(type2)),
+                Self::Any => true,
+                Self::Union(_) => other == self,
+                Self::Scission(_) => other == self,
+                _ => false
+            }
+            Self::Map(types1) => match other {
+                Self::Map(types2) => types2.0.iter().all(|type2| types1.0.contains(type2))
+                    && types2.1.iter().all(|type2| types1.1.contains(type2)),
                 Self::Any => true,
                 Self::Union(_) => other == self,
                 Self::Scission(_) => other == self,
@@ -410,13 +840,41 @@ impl PartialEq for Type {
                 _ => false
             }
             Self::Rule(name1) => match other {
-                // todo rule comp, node comp
                 Self::Rule(name2) => name1 == name2,
                 Self::Any => true,
                 Self::Union(_) => other == self,
                 Self::Scission(_) => other == self,
                 _ => false
             }
+            // `and`/`or` are commutative, so the order of the operands doesn't matter
+            Self::RuleAnd(t1) => match other {
+                Self::RuleAnd(t2) => t1.len() == t2.len() && t1.iter().all(|type1| t2.contains(type1)),
+                Self::Any => true,
+                Self::Union(_) => other == self,
+                Self::Scission(_) => other == self,
+                _ => false
+            }
+            Self::RuleOr(t1) => match other {
+                Self::RuleOr(t2) => t1.len() == t2.len() && t1.iter().all(|type1| t2.contains(type1)),
+                Self::Any => true,
+                Self::Union(_) => other == self,
+                Self::Scission(_) => other == self,
+                _ => false
+            }
+            Self::RuleNot(t1) => match other {
+                Self::RuleNot(t2) => t1 == t2,
+                Self::Any => true,
+                Self::Union(_) => other == self,
+                Self::Scission(_) => other == self,
+                _ => false
+            }
+            Self::Shape(name1) => match other {
+                Self::Shape(name2) => name1 == name2,
+                Self::Any => true,
+                Self::Union(_) => other == self,
+                Self::Scission(_) => other == self,
+                _ => false
+            }
             Self::Type => match other {
                 Self::Type => true,
                 Self::Any => true,
@@ -424,6 +882,27 @@ impl PartialEq for Type {
                 Self::Scission(_) => other == self,
                 _ => false
             }
+            Self::Bytes => match other {
+                Self::Bytes => true,
+                Self::Any => true,
+                Self::Union(_) => other == self,
+                Self::Scission(_) => other == self,
+                _ => false
+            }
+            Self::Range => match other {
+                Self::Range => true,
+                Self::Any => true,
+                Self::Union(_) => other == self,
+                Self::Scission(_) => other == self,
+                _ => false
+            }
+            Self::Socket => match other {
+                Self::Socket => true,
+                Self::Any => true,
+                Self::Union(_) => other == self,
+                Self::Scission(_) => other == self,
+                _ => false
+            }
             Self::Union(t1) => match other {
                 Self::Union(t2) => {
                     for type1 in t1.iter() {
@@ -0,0 +1,166 @@
+use crate::*;
+
+fn arg(context: &Context, id: &str) -> V {
+    context.get(&id.to_string()).cloned().unwrap_or(V::Null)
+}
+fn arg_values(context: &Context, id: &str) -> Vec<V> {
+    match context.get(&id.to_string()) {
+        Some(V::Vector(values, _)) => values.clone(),
+        _ => vec![]
+    }
+}
+fn vector(values: Vec<V>) -> V {
+    let types = V::create_union(values.clone());
+    V::Vector(values, types)
+}
+/// calls `f` with the arguments, all of them coming from the call at `pos`
+fn call(f: &V, args: Vec<V>, pos: &Position, context: &mut Context) -> Result<V, E> {
+    let poses = vec![pos; args.len()];
+    call_proc(f, args, poses, pos, context)
+}
+/// the type of a proc argument, or of an optional one
+pub fn callable_type(optional: bool) -> Type {
+    let mut types = vec![Type::Proc, Type::ForeignProc];
+    if optional { types.push(Type::Undefined) }
+    Type::create_union(types)
+}
+
+pub fn vec_map(context: &mut Context, pos: &Position) -> Result<V, E> {
+    let f = arg(context, "f");
+    let mut values: Vec<V> = vec![];
+    for v in arg_values(context, "self") {
+        values.push(call(&f, vec![v], pos, context)?);
+    }
+    Ok(vector(values))
+}
+pub fn vec_filter(context: &mut Context, pos: &Position) -> Result<V, E> {
+    let f = arg(context, "f");
+    let mut values: Vec<V> = vec![];
+    for v in arg_values(context, "self") {
+        if V::bool(&call(&f, vec![v.clone()], pos, context)?) == V::Bool(true) { values.push(v) }
+    }
+    Ok(vector(values))
+}
+/// folds the values with `f(acc, value)`, starting with `init` or else the first value,
+/// `null` if there is nothing to fold
+pub fn vec_reduce(context: &mut Context, pos: &Position) -> Result<V, E> {
+    let f = arg(context, "f");
+    let mut values = arg_values(context, "self").into_iter();
+    let mut acc = match arg(context, "init") {
+        V::Null => match values.next() {
+            Some(v) => v,
+            None => return Ok(V::Null)
+        }
+        init => init
+    };
+    for v in values {
+        acc = call(&f, vec![acc, v], pos, context)?;
+    }
+    Ok(acc)
+}
+/// whether `f` (or the value itself without `f`) holds for any value, stopping at the first one
+fn find_truth(context: &mut Context, pos: &Position, truth: bool) -> Result<bool, E> {
+    let f = arg(context, "f");
+    for v in arg_values(context, "self") {
+        let v = if f == V::Null { v } else { call(&f, vec![v], pos, context)? };
+        if V::bool(&v) == V::Bool(truth) { return Ok(true) }
+    }
+    Ok(false)
+}
+pub fn vec_any(context: &mut Context, pos: &Position) -> Result<V, E> {
+    Ok(V::Bool(find_truth(context, pos, true)?))
+}
+pub fn vec_all(context: &mut Context, pos: &Position) -> Result<V, E> {
+    Ok(V::Bool(!find_truth(context, pos, false)?))
+}
+/// the keys of a sort, all of one kind so they always have a total order
+enum SortKeys {
+    Strings(Vec<String>), Ints(Vec<BigInt>), Floats(Vec<f64>)
+}
+/// `0` for strings and `1` for numbers, which can be compared with each other
+fn sort_kind(v: &V) -> Option<u8> {
+    match v {
+        V::String(_) => Some(0),
+        V::Int(_) | V::BigInt(_) | V::Float(_) => Some(1),
+        _ => None
+    }
+}
+/// strings are ordered by their characters, `int`s and `bigint`s exactly and numbers with a `float`
+/// as floats with `nan` last, anything else or strings mixed with numbers can't be sorted
+fn sort_keys(keys: Vec<V>, pos: &Position, context: &mut Context) -> Result<SortKeys, E> {
+    let kind = sort_kind(&keys[0]);
+    if let Some(i) = keys.iter().position(|k| kind.is_none() || sort_kind(k) != kind) {
+        context.trace(pos.clone());
+        return Err(E::Binary(T::LT, keys[0].clone(), keys[if i == 0 { 1 } else { i }].clone()))
+    }
+    if kind == Some(0) {
+        return Ok(SortKeys::Strings(keys.into_iter().map(|k| match k {
+            V::String(v) => v,
+            _ => String::new()
+        }).collect()))
+    }
+    if keys.iter().all(|k| !matches!(k, V::Float(_))) {
+        return Ok(SortKeys::Ints(keys.iter().map(|k| match k {
+            V::BigInt(v) => v.clone(),
+            V::Int(v) => BigInt::from_i64(*v),
+            _ => BigInt::from_i64(0)
+        }).collect()))
+    }
+    Ok(SortKeys::Floats(keys.iter().map(|k| match k {
+        V::Int(v) => *v as f64,
+        V::BigInt(v) => v.to_f64(),
+        V::Float(v) => *v,
+        _ => 0.0
+    }).collect()))
+}
+/// a sorted copy, compared on the values or on what `key` returns for them,
+/// equal values keep their order
+pub fn vec_sort(context: &mut Context, pos: &Position) -> Result<V, E> {
+    let key = arg(context, "key");
+    let values = arg_values(context, "self");
+    if values.len() < 2 { return Ok(vector(values)) }
+    let mut keys: Vec<V> = vec![];
+    for v in values.iter() {
+        keys.push(if key == V::Null { v.clone() } else { call(&key, vec![v.clone()], pos, context)? });
+    }
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    match sort_keys(keys, pos, context)? {
+        SortKeys::Strings(keys) => order.sort_by(|a, b| keys[*a].cmp(&keys[*b])),
+        SortKeys::Ints(keys) => order.sort_by(|a, b| keys[*a].cmp(&keys[*b])),
+        SortKeys::Floats(keys) => order.sort_by(|a, b| keys[*a].total_cmp(&keys[*b]))
+    }
+    Ok(vector(order.into_iter().map(|i| values[i].clone()).collect()))
+}
+pub fn vec_reverse(context: &mut Context, pos: &Position) -> Result<V, E> {
+    Ok(vector(arg_values(context, "self").into_iter().rev().collect()))
+}
+/// pairs up the values of both vectors as tuples, as long as the shorter one
+pub fn vec_zip(context: &mut Context, pos: &Position) -> Result<V, E> {
+    let pairs = arg_values(context, "self").into_iter().zip(arg_values(context, "other"));
+    Ok(vector(pairs.map(|(a, b)| V::Tuple(vec![a, b])).collect()))
+}
+/// the values as `(index, value)` tuples
+pub fn vec_enumerate(context: &mut Context, pos: &Position) -> Result<V, E> {
+    let values = arg_values(context, "self").into_iter().enumerate();
+    Ok(vector(values.map(|(i, v)| V::Tuple(vec![V::Int(i as i64), v])).collect()))
+}
+/// adds up the values with `+`, `0` if there are none
+pub fn vec_sum(context: &mut Context, pos: &Position) -> Result<V, E> {
+    let mut sum = V::Int(0);
+    for v in arg_values(context, "self") {
+        sum = binary(&T::Add, &sum, &v, pos, context)?;
+    }
+    Ok(sum)
+}
+pub fn vec_contains(context: &mut Context, pos: &Position) -> Result<V, E> {
+    let x = arg(context, "x");
+    Ok(V::Bool(arg_values(context, "self").contains(&x)))
+}
+/// the index of the first value equal to `x`, `null` if there is none
+pub fn vec_index_of(context: &mut Context, pos: &Position) -> Result<V, E> {
+    let x = arg(context, "x");
+    match arg_values(context, "self").iter().position(|v| *v == x) {
+        Some(i) => Ok(V::Int(i as i64)),
+        None => Ok(V::Null)
+    }
+}